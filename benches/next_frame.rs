@@ -0,0 +1,57 @@
+// Measures Organism::next_frame for bodies of 1, 10 and 100 squares.
+//
+// Run with `cargo bench --bench next_frame`. Every organism is grown from the
+// same seed and each batch starts from a fresh clone, so runs on the same
+// machine are comparable. The organisms never die or run out of energy during
+// a batch, and reproduce on every frame once past puberty, so the numbers
+// include building and validating offspring.
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use opus::organism::{Body, Genome, Organism};
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+const SEED: u64 = 42;
+
+fn organism_with_squares(square_count: usize) -> Organism {
+    let mut rng = Pcg64::seed_from_u64(SEED);
+    let bodies = vec![
+        Body::random(square_count, &mut rng),
+        Body::random(square_count, &mut rng),
+    ];
+    let genome = Genome::builder()
+        .body_states(bodies)
+        .build()
+        .expect("one gene per attribute");
+    let mut organism = Organism::new(0, genome).expect("genome has body states");
+    organism.update_attributes(|attributes| {
+        attributes.max_energy = isize::MAX;
+        attributes.max_age = isize::MAX;
+        attributes.puberty_age = 0;
+        attributes.reproduction_rate = 1.0;
+    });
+    organism.gain_energy(isize::MAX / 2);
+    organism
+}
+
+fn next_frame(c: &mut Criterion) {
+    let mut group = c.benchmark_group("next_frame");
+    for square_count in [1, 10, 100] {
+        let organism = organism_with_squares(square_count);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(square_count),
+            &organism,
+            |b, organism| {
+                let mut rng = Pcg64::seed_from_u64(SEED);
+                b.iter_batched_ref(
+                    || organism.clone(),
+                    |organism| black_box(organism.next_frame(1, &mut rng)),
+                    BatchSize::SmallInput,
+                );
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, next_frame);
+criterion_main!(benches);
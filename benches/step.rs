@@ -0,0 +1,78 @@
+// Measures World::step on 50,000 organisms, once on a single thread and once
+// on rayon's default pool.
+//
+// Run with `cargo bench --bench step --features rayon`. Both runs step the
+// same seeded population from the same starting state, and give the same
+// result, so the difference is down to parallelism alone.
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use nalgebra::Vector2;
+use opus::organism::{Body, Genome, Organism};
+use opus::world::World;
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+const SEED: u64 = 42;
+const POPULATION: usize = 50_000;
+// Organisms sit this many cells apart, so their bodies start out apart.
+const SPACING: usize = 4;
+
+fn population(size: Vector2<usize>) -> Vec<Organism> {
+    let mut rng = Pcg64::seed_from_u64(SEED);
+    let columns = size.x / SPACING;
+    (0..POPULATION)
+        .map(|index| {
+            let genome = Genome::builder()
+                .body_states(vec![Body::random(3, &mut rng), Body::random(3, &mut rng)])
+                .build()
+                .expect("one gene per attribute");
+            let mut organism =
+                Organism::new(index as isize, genome).expect("genome has body states");
+            organism.reseed(SEED);
+            let location = Vector2::new(
+                ((index % columns) * SPACING) as isize,
+                ((index / columns) * SPACING) as isize,
+            );
+            organism
+                .set_location(location, size)
+                .expect("population fits the world");
+            organism
+        })
+        .collect()
+}
+
+fn step(c: &mut Criterion) {
+    let side = (POPULATION as f64).sqrt().ceil() as usize * SPACING;
+    let size = Vector2::new(side, side);
+    let organisms = population(size);
+    let world = || {
+        let mut world = World::from_seed(size, SEED);
+        world.organisms = organisms.clone();
+        world.rebuild_spatial_grid();
+        world
+    };
+    let serial = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .expect("a single worker thread");
+
+    let mut group = c.benchmark_group("step");
+    group.sample_size(10);
+    group.bench_function("serial", |b| {
+        b.iter_batched_ref(
+            world,
+            |world| serial.install(|| black_box(world.step())),
+            BatchSize::LargeInput,
+        );
+    });
+    group.bench_function("parallel", |b| {
+        b.iter_batched_ref(
+            world,
+            |world| black_box(world.step()),
+            BatchSize::LargeInput,
+        );
+    });
+    group.finish();
+}
+
+criterion_group!(benches, step);
+criterion_main!(benches);
@@ -0,0 +1,106 @@
+use crate::organism::{Attribute, Genome, OrganismError};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+#[cfg(feature = "ron")]
+use std::path::Path;
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GenomeTemplate {
+    pub genome: Genome,
+    pub count: usize,
+}
+
+// Settings a world applies to the organisms it creates. Genes shift
+// `default_attributes`, so changing it moves the baseline of every organism
+// spawned into the world.
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct WorldConfig {
+    pub default_attributes: Attribute,
+}
+
+// Initial world description: grid size, an optional seed, and how many
+// organisms to seed from each genome template.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScenarioConfig {
+    pub size: (usize, usize),
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub seed: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub world: WorldConfig,
+    pub templates: Vec<GenomeTemplate>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(String),
+    EmptySize,
+    EmptyTemplate {
+        template: usize,
+    },
+    InvalidGenome {
+        template: usize,
+        error: OrganismError,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "could not read config: {}", err),
+            ConfigError::Parse(msg) => write!(f, "could not parse config: {}", msg),
+            ConfigError::EmptySize => write!(f, "world size must be positive on both axes"),
+            ConfigError::EmptyTemplate { template } => {
+                write!(f, "template {} must seed at least one organism", template)
+            }
+            ConfigError::InvalidGenome { template, error } => {
+                write!(f, "template {} has an invalid genome: {}", template, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io(err) => Some(err),
+            ConfigError::InvalidGenome { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(err: io::Error) -> Self {
+        ConfigError::Io(err)
+    }
+}
+
+impl ScenarioConfig {
+    #[cfg(feature = "ron")]
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path)?;
+        let config: ScenarioConfig =
+            ron::from_str(&text).map_err(|err| ConfigError::Parse(err.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.size.0 == 0 || self.size.1 == 0 {
+            return Err(ConfigError::EmptySize);
+        }
+        for (template, entry) in self.templates.iter().enumerate() {
+            if entry.count == 0 {
+                return Err(ConfigError::EmptyTemplate { template });
+            }
+        }
+        Ok(())
+    }
+}
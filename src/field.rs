@@ -0,0 +1,41 @@
+use nalgebra::Vector2;
+
+// Row-major index of a cell, or None when the cell is off the grid.
+pub fn cell_index(size: Vector2<usize>, cell: (isize, isize)) -> Option<usize> {
+    let (x, y) = cell;
+    if x < 0 || y < 0 || x as usize >= size.x || y as usize >= size.y {
+        return None;
+    }
+    Some(y as usize * size.x + x as usize)
+}
+
+// One diffusion step: every cell becomes the mean of itself and its four
+// neighbours. Off-grid neighbours either wrap around or are clamped to the
+// edge cell, and both keep the total energy in the field unchanged. A field
+// that does not match the grid size is left alone.
+pub fn diffuse(field: &mut [f64], size: Vector2<usize>, wrap: bool) {
+    if field.len() != size.x * size.y {
+        return;
+    }
+    let width = size.x as isize;
+    let height = size.y as isize;
+    let neighbor = |x: isize, y: isize| {
+        let (x, y) = if wrap {
+            (x.rem_euclid(width), y.rem_euclid(height))
+        } else {
+            (x.clamp(0, width - 1), y.clamp(0, height - 1))
+        };
+        (y * width + x) as usize
+    };
+    let previous = field.to_vec();
+    for y in 0..height {
+        for x in 0..width {
+            let total = previous[neighbor(x, y)]
+                + previous[neighbor(x - 1, y)]
+                + previous[neighbor(x + 1, y)]
+                + previous[neighbor(x, y - 1)]
+                + previous[neighbor(x, y + 1)];
+            field[neighbor(x, y)] = total / 5.0;
+        }
+    }
+}
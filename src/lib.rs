@@ -1,2 +1,13 @@
+pub mod config;
+pub mod field;
+pub mod metrics;
+pub mod mutation;
+pub mod observer;
 pub mod organism;
+pub mod render;
+pub mod shared;
+pub mod simulation;
+pub mod snapshot;
+pub mod spatial;
+pub mod stats;
 pub mod world;
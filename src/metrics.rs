@@ -0,0 +1,116 @@
+use crate::world::World;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+pub const CSV_HEADER: &str = "tick,population,mean_energy,mean_age,mean_size,species_count";
+pub const DEFAULT_SPECIES_THRESHOLD: f64 = 3.0;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MetricsRow {
+    pub tick: u64,
+    pub population: usize,
+    pub mean_energy: Option<f64>,
+    pub mean_age: Option<f64>,
+    pub mean_size: Option<f64>,
+    pub species_count: usize,
+}
+
+impl MetricsRow {
+    pub fn from_world(world: &World, species_threshold: f64) -> Self {
+        let stats = world.stats();
+        Self {
+            tick: world.tick(),
+            population: stats.population,
+            mean_energy: stats.energy.map(|summary| summary.mean),
+            mean_age: stats.age.map(|summary| summary.mean),
+            mean_size: stats.body_size.map(|summary| summary.mean),
+            species_count: world.cluster_species(species_threshold).len(),
+        }
+    }
+
+    // Means of an empty population are left blank.
+    pub fn write_csv(&self, writer: &mut impl Write) -> io::Result<()> {
+        let mean = |value: Option<f64>| value.map(|value| value.to_string()).unwrap_or_default();
+        writeln!(
+            writer,
+            "{},{},{},{},{},{}",
+            self.tick,
+            self.population,
+            mean(self.mean_energy),
+            mean(self.mean_age),
+            mean(self.mean_size),
+            self.species_count
+        )
+    }
+}
+
+// Records one row per call to `record`, keyed by the world's tick. A
+// recorder built with `streaming` writes rows straight to its writer instead
+// of keeping them in memory.
+pub struct MetricsRecorder {
+    pub species_threshold: f64,
+    rows: Vec<MetricsRow>,
+    sink: Option<Box<dyn Write + Send>>,
+}
+
+impl Default for MetricsRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsRecorder {
+    pub fn new() -> Self {
+        Self {
+            species_threshold: DEFAULT_SPECIES_THRESHOLD,
+            rows: Vec::new(),
+            sink: None,
+        }
+    }
+
+    pub fn streaming(writer: impl Write + Send + 'static) -> io::Result<Self> {
+        let mut writer: Box<dyn Write + Send> = Box::new(writer);
+        writeln!(writer, "{}", CSV_HEADER)?;
+        Ok(Self {
+            sink: Some(writer),
+            ..Self::new()
+        })
+    }
+
+    pub fn record(&mut self, world: &World) -> io::Result<()> {
+        let row = MetricsRow::from_world(world, self.species_threshold);
+        match &mut self.sink {
+            Some(sink) => row.write_csv(sink),
+            None => {
+                self.rows.push(row);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn rows(&self) -> &[MetricsRow] {
+        &self.rows
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match &mut self.sink {
+            Some(sink) => sink.flush(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        writeln!(writer, "{}", CSV_HEADER)?;
+        for row in &self.rows {
+            row.write_csv(writer)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_csv(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+}
@@ -0,0 +1,124 @@
+use crate::organism::Organism;
+use rand::{Rng, RngCore};
+
+// A mutation operator, applied when an organism mutates and to every
+// offspring. Generators are passed as trait objects so operators can be boxed
+// and chained.
+pub trait Mutator {
+    fn mutate(&self, organism: &mut Organism, rng: &mut dyn RngCore);
+
+    // Energy the organism pays before `mutate` runs on it. By default that is
+    // mutation_cost for every square of every body state.
+    fn cost(&self, organism: &Organism) -> f64 {
+        let attributes = organism.attributes();
+        let squares: usize = attributes.body_states.iter().map(|body| body.size()).sum();
+        attributes.mutation_cost as f64 * squares as f64
+    }
+}
+
+// The built-in mutation: body jitter, growth and shrinkage, rotation,
+// reflection and gene perturbation.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct DefaultMutator;
+
+impl Mutator for DefaultMutator {
+    fn mutate(&self, organism: &mut Organism, mut rng: &mut dyn RngCore) {
+        organism.mutate(&mut rng);
+    }
+}
+
+// Leaves organisms exactly as they are.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct NoMutation;
+
+impl Mutator for NoMutation {
+    fn mutate(&self, _organism: &mut Organism, _rng: &mut dyn RngCore) {}
+
+    fn cost(&self, _organism: &Organism) -> f64 {
+        0.0
+    }
+}
+
+// Nudges every square of every body state, keeping each body in one piece.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Jitter;
+
+impl Mutator for Jitter {
+    fn mutate(&self, organism: &mut Organism, mut rng: &mut dyn RngCore) {
+        for index in 0..organism.attributes.body_states.len() {
+            let body = &organism.attributes.body_states[index];
+            let mut jittered = organism.jitter_body(body, &mut rng);
+            jittered.canonicalize();
+            organism.attributes.body_states[index] = jittered;
+        }
+    }
+}
+
+// Grows every body state by one square, up to max_size.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct AddSquare;
+
+impl Mutator for AddSquare {
+    fn mutate(&self, organism: &mut Organism, mut rng: &mut dyn RngCore) {
+        let max_size = organism.attributes.max_size.max(1) as usize;
+        for body in &mut organism.attributes.body_states {
+            if body.size() < max_size && body.grow(&mut rng) {
+                body.canonicalize();
+            }
+        }
+    }
+}
+
+// Perturbs each gene with a chance of the mutation rate, then re-derives the
+// attributes genes control.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct GeneValue;
+
+impl Mutator for GeneValue {
+    fn mutate(&self, organism: &mut Organism, mut rng: &mut dyn RngCore) {
+        let mutation_rate = organism.attributes.mutation_rate;
+        let chance = (mutation_rate as f64).clamp(0.0, 1.0);
+        let mut changed = false;
+        for gene in &mut organism.genome.genes {
+            if rng.gen_bool(chance) {
+                gene.perturb(mutation_rate, &mut rng);
+                changed = true;
+            }
+        }
+        if changed {
+            organism.rederive_attributes();
+        }
+    }
+}
+
+// Runs several operators one after another, in the order they were added.
+#[derive(Default)]
+pub struct Chain {
+    operators: Vec<Box<dyn Mutator + Send + Sync>>,
+}
+
+impl Chain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn then(mut self, operator: impl Mutator + Send + Sync + 'static) -> Self {
+        self.operators.push(Box::new(operator));
+        self
+    }
+}
+
+impl Mutator for Chain {
+    fn mutate(&self, organism: &mut Organism, rng: &mut dyn RngCore) {
+        for operator in &self.operators {
+            operator.mutate(organism, rng);
+        }
+    }
+
+    fn cost(&self, organism: &Organism) -> f64 {
+        self.operators
+            .iter()
+            .map(|operator| operator.cost(organism))
+            .sum()
+    }
+}
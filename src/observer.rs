@@ -0,0 +1,13 @@
+use crate::organism::Organism;
+
+// Hooks called by World::step_with_observer as events happen. Every method
+// does nothing by default, so observers only implement what they need.
+pub trait SimObserver {
+    fn on_birth(&mut self, _child: &Organism) {}
+    fn on_death(&mut self, _id: isize) {}
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct NoopObserver;
+
+impl SimObserver for NoopObserver {}
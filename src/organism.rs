@@ -1,45 +1,419 @@
+use crate::field;
+use crate::mutation::{DefaultMutator, Mutator};
 use nalgebra::Vector2;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BodySquare {
     pub position: Vector2<f64>,
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Body {
     squares: Vec<BodySquare>,
+    #[cfg_attr(feature = "serde", serde(default = "default_adjacency_threshold"))]
+    adjacency_threshold: f64,
+}
+
+// Squares closer than this count as touching. The default admits diagonal
+// neighbours; anything between 1.0 and sqrt(2) allows orthogonal ones only.
+pub const DEFAULT_ADJACENCY_THRESHOLD: f64 = 1.5;
+
+#[cfg(feature = "serde")]
+fn default_adjacency_threshold() -> f64 {
+    DEFAULT_ADJACENCY_THRESHOLD
+}
+
+impl Default for Body {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Body {
     pub fn new() -> Self {
         Self {
             squares: Vec::new(),
+            adjacency_threshold: DEFAULT_ADJACENCY_THRESHOLD,
         }
     }
 
+    pub fn with_adjacency_threshold(mut self, adjacency_threshold: f64) -> Self {
+        self.adjacency_threshold = adjacency_threshold;
+        self
+    }
+
+    pub fn adjacency_threshold(&self) -> f64 {
+        self.adjacency_threshold
+    }
+
     pub fn add_square(&mut self, square: BodySquare) {
         self.squares.push(square);
     }
 
+    // Like add_square, but refuses a square that would overlap an existing one.
+    pub fn try_add_square(&mut self, square: BodySquare) -> bool {
+        if self.overlaps(&square) {
+            return false;
+        }
+        self.add_square(square);
+        true
+    }
+
+    pub fn squares(&self) -> &[BodySquare] {
+        &self.squares
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, BodySquare> {
+        self.squares.iter()
+    }
+
+    pub fn size(&self) -> usize {
+        self.squares.len()
+    }
+
+    // The lattice cell each square sits on, rounded to the nearest point.
+    pub fn cells(&self) -> Vec<Vector2<i32>> {
+        self.squares
+            .iter()
+            .map(|square| square.position.map(|v| v.round() as i32))
+            .collect()
+    }
+
+    // Moves every square onto the nearest lattice point.
+    pub fn snap_to_grid(&mut self) {
+        for square in &mut self.squares {
+            square.position = square.position.map(f64::round);
+        }
+    }
+
+    pub fn bounding_box(&self) -> Option<(Vector2<f64>, Vector2<f64>)> {
+        let first = self.squares.first()?.position;
+        Some(
+            self.squares
+                .iter()
+                .fold((first, first), |(min, max), square| {
+                    (min.inf(&square.position), max.sup(&square.position))
+                }),
+        )
+    }
+
+    pub fn center_of_mass(&self) -> Option<Vector2<f64>> {
+        if self.squares.is_empty() {
+            return None;
+        }
+        let total: Vector2<f64> = self.squares.iter().map(|square| square.position).sum();
+        Some(total / self.squares.len() as f64)
+    }
+
+    // Cells of the bounding box per square. A single square or any body
+    // that fills its bounding box scores 1, and sprawling bodies score higher.
+    pub fn sprawl(&self) -> Option<f64> {
+        let (min, max) = self.bounding_box()?;
+        let extent = max - min + Vector2::new(1.0, 1.0);
+        Some(extent.x * extent.y / self.squares.len() as f64)
+    }
+
+    // Share of squares whose mirror image across the vertical line through
+    // the centroid lands on another square. Bilaterally symmetric bodies
+    // score 1, and an empty body counts as symmetric.
+    pub fn symmetry_score(&self) -> f64 {
+        let Some(center) = self.center_of_mass() else {
+            return 1.0;
+        };
+        let mirrored = self
+            .squares
+            .iter()
+            .filter(|square| {
+                self.overlaps(&BodySquare {
+                    position: Vector2::new(2.0 * center.x - square.position.x, square.position.y),
+                })
+            })
+            .count();
+        mirrored as f64 / self.squares.len() as f64
+    }
+
+    // Number of square edges not shared with another square. With the square
+    // count as area this gives shape descriptors such as compactness.
+    pub fn perimeter(&self) -> usize {
+        self.squares
+            .iter()
+            .map(|square| {
+                ORTHOGONAL_OFFSETS
+                    .iter()
+                    .filter(|&&(dx, dy)| {
+                        !self.overlaps(&BodySquare {
+                            position: square.position + Vector2::new(dx, dy),
+                        })
+                    })
+                    .count()
+            })
+            .sum()
+    }
+
+    pub fn has_overlaps(&self) -> bool {
+        self.squares.iter().enumerate().any(|(index, square)| {
+            self.squares[index + 1..]
+                .iter()
+                .any(|other| squares_overlap(square, other))
+        })
+    }
+
+    fn overlaps(&self, square: &BodySquare) -> bool {
+        self.squares
+            .iter()
+            .any(|existing| squares_overlap(square, existing))
+    }
+
     pub fn check_blueprint_validity(&self, proposed_squares: &[BodySquare]) -> bool {
         for square in proposed_squares {
             if !self.is_adjacent(square) {
                 return false;
             }
         }
-        true
+        let proposed = Body {
+            squares: proposed_squares.to_vec(),
+            adjacency_threshold: self.adjacency_threshold,
+        };
+        proposed.is_connected()
     }
 
     fn is_adjacent(&self, square: &BodySquare) -> bool {
         self.squares.iter().any(|existing_square| {
-            let diff = square.position - existing_square.position;
-            diff.magnitude() < 1.5
+            squares_adjacent(square, existing_square, self.adjacency_threshold)
+        })
+    }
+
+    pub fn is_connected(&self) -> bool {
+        let Some(first) = self.squares.first() else {
+            return true;
+        };
+        let mut reached = vec![false; self.squares.len()];
+        reached[0] = true;
+        let mut frontier = vec![*first];
+        while let Some(current) = frontier.pop() {
+            for (index, square) in self.squares.iter().enumerate() {
+                if !reached[index] && squares_adjacent(&current, square, self.adjacency_threshold) {
+                    reached[index] = true;
+                    frontier.push(*square);
+                }
+            }
+        }
+        reached.into_iter().all(|reached| reached)
+    }
+
+    // Grows a connected blob from a single square at the origin by attaching
+    // each new square next to an existing one. A count of zero gives an empty
+    // body.
+    pub fn random(square_count: usize, rng: &mut impl Rng) -> Body {
+        let mut body = Body::new();
+        if square_count == 0 {
+            return body;
+        }
+        body.add_square(BodySquare {
+            position: Vector2::new(0.0, 0.0),
+        });
+        while body.size() < square_count && body.grow(rng) {}
+        body.canonicalize();
+        body
+    }
+
+    // Sorts squares by x, then y, so bodies of the same shape compare equal
+    // however they were built.
+    pub fn canonicalize(&mut self) {
+        self.squares.sort_by(|a, b| {
+            a.position
+                .x
+                .total_cmp(&b.position.x)
+                .then(a.position.y.total_cmp(&b.position.y))
+        });
+    }
+
+    // Whether every square of `other` has a square of this body on it.
+    pub fn covers(&self, other: &Body) -> bool {
+        other.squares.iter().all(|square| self.overlaps(square))
+    }
+
+    // Adds the first square of `target` that this body lacks and that touches
+    // it, and reports whether one was added.
+    pub fn grow_toward(&mut self, target: &Body) -> bool {
+        let next = target
+            .squares
+            .iter()
+            .find(|square| !self.overlaps(square) && self.is_adjacent(square));
+        match next {
+            Some(&square) => {
+                self.add_square(square);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Number of squares that differ between two bodies: squares of either body
+    // with no counterpart in the other within the overlap tolerance, taking
+    // whichever body has more of them.
+    pub fn changed_squares(&self, other: &Body) -> usize {
+        let unmatched = |from: &Body, to: &Body| {
+            from.squares
+                .iter()
+                .filter(|square| !to.overlaps(square))
+                .count()
+        };
+        unmatched(self, other).max(unmatched(other, self))
+    }
+
+    // Square-by-square interpolation towards `other`, or None when the bodies
+    // have different square counts. Squares are paired in stored order.
+    fn lerp(&self, other: &Body, t: f32) -> Option<Body> {
+        if self.squares.len() != other.squares.len() {
+            return None;
+        }
+        let t = t as f64;
+        let squares = self
+            .squares
+            .iter()
+            .zip(&other.squares)
+            .map(|(a, b)| BodySquare {
+                position: a.position * (1.0 - t) + b.position * t,
+            })
+            .collect();
+        Some(Body {
+            squares,
+            adjacency_threshold: self.adjacency_threshold,
         })
     }
+
+    // Rotates the body by quarter turns counterclockwise about its centre of
+    // mass, rounded to the nearest whole cell so bodies on the grid stay on it.
+    pub fn rotate(&self, quarter_turns: u8) -> Body {
+        self.transform_about_center(|offset| match quarter_turns % 4 {
+            0 => offset,
+            1 => Vector2::new(-offset.y, offset.x),
+            2 => -offset,
+            _ => Vector2::new(offset.y, -offset.x),
+        })
+    }
+
+    // Mirrors the body left to right about the same pivot as rotate.
+    pub fn reflect_x(&self) -> Body {
+        self.transform_about_center(|offset| Vector2::new(-offset.x, offset.y))
+    }
+
+    // Mirrors the body top to bottom about the same pivot as rotate.
+    pub fn reflect_y(&self) -> Body {
+        self.transform_about_center(|offset| Vector2::new(offset.x, -offset.y))
+    }
+
+    // Every transform used here is an isometry, so connectivity and overlaps
+    // are unchanged.
+    fn transform_about_center(&self, transform: impl Fn(Vector2<f64>) -> Vector2<f64>) -> Body {
+        let Some(center) = self.center_of_mass() else {
+            return self.clone();
+        };
+        let pivot = center.map(f64::round);
+        let squares = self
+            .squares
+            .iter()
+            .map(|square| BodySquare {
+                position: pivot + transform(square.position - pivot),
+            })
+            .collect();
+        let mut body = Body {
+            squares,
+            adjacency_threshold: self.adjacency_threshold,
+        };
+        body.canonicalize();
+        body
+    }
+
+    // Attaches a square next to a random existing one, never on top of one.
+    // Only neighbours within the adjacency threshold are considered.
+    pub(crate) fn grow(&mut self, rng: &mut impl Rng) -> bool {
+        let mut candidates = Vec::new();
+        for square in &self.squares {
+            for (dx, dy) in NEIGHBOR_OFFSETS {
+                let candidate = BodySquare {
+                    position: square.position + Vector2::new(dx, dy),
+                };
+                if squares_adjacent(square, &candidate, self.adjacency_threshold)
+                    && !self.overlaps(&candidate)
+                {
+                    candidates.push(candidate);
+                }
+            }
+        }
+        if candidates.is_empty() {
+            return false;
+        }
+        let square = candidates[rng.gen_range(0..candidates.len())];
+        self.add_square(square);
+        true
+    }
+
+    // Removes a random square whose loss keeps the body in one piece. The last
+    // square is never removed.
+    fn shrink(&mut self, rng: &mut impl Rng) -> bool {
+        if self.squares.len() <= 1 {
+            return false;
+        }
+        let removable: Vec<usize> = (0..self.squares.len())
+            .filter(|&index| {
+                let mut remaining = self.clone();
+                remaining.squares.remove(index);
+                remaining.is_connected()
+            })
+            .collect();
+        if removable.is_empty() {
+            return false;
+        }
+        self.squares
+            .remove(removable[rng.gen_range(0..removable.len())]);
+        true
+    }
+}
+
+impl<'a> IntoIterator for &'a Body {
+    type Item = &'a BodySquare;
+    type IntoIter = std::slice::Iter<'a, BodySquare>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+const NEIGHBOR_OFFSETS: [(f64, f64); 8] = [
+    (-1.0, -1.0),
+    (0.0, -1.0),
+    (1.0, -1.0),
+    (-1.0, 0.0),
+    (1.0, 0.0),
+    (-1.0, 1.0),
+    (0.0, 1.0),
+    (1.0, 1.0),
+];
+
+const ORTHOGONAL_OFFSETS: [(f64, f64); 4] = [(1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0)];
+
+// Squares closer than this are treated as sitting in the same cell.
+const OVERLAP_TOLERANCE: f64 = 0.5;
+
+fn squares_adjacent(a: &BodySquare, b: &BodySquare, threshold: f64) -> bool {
+    (a.position - b.position).magnitude() < threshold
+}
+
+fn squares_overlap(a: &BodySquare, b: &BodySquare) -> bool {
+    (a.position - b.position).magnitude() < OVERLAP_TOLERANCE
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AttributeType {
     MaxEnergy(isize),
     MaxAge(isize),
@@ -51,7 +425,109 @@ pub enum AttributeType {
     Metabolism(f32),
 }
 
+impl AttributeType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AttributeType::MaxEnergy(_) => "max_energy",
+            AttributeType::MaxAge(_) => "max_age",
+            AttributeType::MaxSize(_) => "max_size",
+            AttributeType::ReproductionRate(_) => "reproduction_rate",
+            AttributeType::MutationRate(_) => "mutation_rate",
+            AttributeType::PubertyAge(_) => "puberty_age",
+            AttributeType::BodyStates(_) => "body_states",
+            AttributeType::Metabolism(_) => "metabolism",
+        }
+    }
+
+    // Interpolates towards an attribute of the same kind, or None for a
+    // different kind. Body states go square by square where the counts match,
+    // and otherwise come from whichever end `t` is closer to.
+    fn lerp(&self, other: &AttributeType, t: f32) -> Option<AttributeType> {
+        let lerped = match (self, other) {
+            (AttributeType::MaxEnergy(a), AttributeType::MaxEnergy(b)) => {
+                AttributeType::MaxEnergy(lerp_whole(*a, *b, t))
+            }
+            (AttributeType::MaxAge(a), AttributeType::MaxAge(b)) => {
+                AttributeType::MaxAge(lerp_whole(*a, *b, t))
+            }
+            (AttributeType::MaxSize(a), AttributeType::MaxSize(b)) => {
+                AttributeType::MaxSize(lerp_whole(*a, *b, t))
+            }
+            (AttributeType::PubertyAge(a), AttributeType::PubertyAge(b)) => {
+                AttributeType::PubertyAge(lerp_whole(*a, *b, t))
+            }
+            (AttributeType::ReproductionRate(a), AttributeType::ReproductionRate(b)) => {
+                AttributeType::ReproductionRate(lerp(*a, *b, t))
+            }
+            (AttributeType::MutationRate(a), AttributeType::MutationRate(b)) => {
+                AttributeType::MutationRate(lerp(*a, *b, t))
+            }
+            (AttributeType::Metabolism(a), AttributeType::Metabolism(b)) => {
+                AttributeType::Metabolism(lerp(*a, *b, t))
+            }
+            (AttributeType::BodyStates(a), AttributeType::BodyStates(b)) => {
+                let nearer = if t < 0.5 { a } else { b };
+                if a.len() != b.len() {
+                    AttributeType::BodyStates(nearer.clone())
+                } else {
+                    let states = a
+                        .iter()
+                        .zip(b)
+                        .zip(nearer)
+                        .map(|((a, b), nearer)| a.lerp(b, t).unwrap_or_else(|| nearer.clone()))
+                        .collect();
+                    AttributeType::BodyStates(states)
+                }
+            }
+            _ => return None,
+        };
+        Some(lerped)
+    }
+
+    fn difference(&self, other: &AttributeType) -> f64 {
+        match (self, other) {
+            (AttributeType::MaxEnergy(a), AttributeType::MaxEnergy(b))
+            | (AttributeType::MaxAge(a), AttributeType::MaxAge(b))
+            | (AttributeType::MaxSize(a), AttributeType::MaxSize(b))
+            | (AttributeType::PubertyAge(a), AttributeType::PubertyAge(b)) => {
+                normalized_difference(*a as f64, *b as f64)
+            }
+            (AttributeType::ReproductionRate(a), AttributeType::ReproductionRate(b))
+            | (AttributeType::MutationRate(a), AttributeType::MutationRate(b))
+            | (AttributeType::Metabolism(a), AttributeType::Metabolism(b)) => {
+                normalized_difference(*a as f64, *b as f64)
+            }
+            (AttributeType::BodyStates(a), AttributeType::BodyStates(b)) => {
+                let squares = |states: &[Body]| states.iter().map(Body::size).sum::<usize>();
+                normalized_difference(squares(a) as f64, squares(b) as f64)
+            }
+            _ => 1.0,
+        }
+    }
+}
+
+// Written so that t = 0 and t = 1 give back exactly a and b.
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a * (1.0 - t) + b * t
+}
+
+fn lerp_whole(a: isize, b: isize, t: f32) -> isize {
+    let t = t as f64;
+    (a as f64 * (1.0 - t) + b as f64 * t).round() as isize
+}
+
+fn normalized_difference(a: f64, b: f64) -> f64 {
+    let scale = a.abs() + b.abs();
+    if scale == 0.0 {
+        0.0
+    } else {
+        (a - b).abs() / scale
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Attribute {
     pub max_energy: isize,
     pub max_age: isize,
@@ -61,9 +537,133 @@ pub struct Attribute {
     pub puberty_age: isize,
     pub body_states: Vec<Body>,
     pub metabolism: f32,
+    pub movement_cost_factor: f32,
+    pub movement_scale: f32,
+    pub metabolism_exponent: f32,
+    pub min_reproduction_energy: isize,
+    pub split_fraction: f32,
+    pub senescence_factor: f32,
+    pub transition_cost_factor: f32,
+    pub movement_mode: MovementMode,
+    pub compactness_factor: f32,
+    pub mortality_model: MortalityModel,
+    pub cycle_mode: CycleMode,
+    pub max_mutation_rate: f32,
+    pub mutation_rate_regression: f32,
+    // Keeps body squares on lattice points through mutation.
+    pub grid_aligned: bool,
+    // Share of max_energy a new organism starts with.
+    pub starting_energy_fraction: f32,
+    pub behavior_mode: BehaviorMode,
+    // Energy charged per body square jittered by a mutation.
+    pub mutation_cost: f32,
+}
+
+// How an organism steps through its body states.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CycleMode {
+    // 0, 1, 2, 0, 1, 2, ...
+    #[default]
+    Forward,
+    // 0, 1, 2, 1, 0, 1, ...
+    PingPong,
+}
+
+// How an organism picks the body state for the next frame.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BehaviorMode {
+    // Always follow the cycle mode.
+    #[default]
+    Cycle,
+    // Hold `body_state` while hunger is at or above `hunger`, then pick the
+    // cycle up where it left off.
+    FeedWhenHungry {
+        body_state: usize,
+        hunger: f32,
+    },
+}
+
+// What an organism remembers between frames.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BehaviorState {
+    // 0 when energy is full, 1 when it is gone.
+    pub hunger: f32,
+    // Age at which the organism last moved.
+    pub last_moved: isize,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MortalityModel {
+    // Death exactly at max_age.
+    #[default]
+    Hard,
+    // A Gompertz-like chance of death each frame that grows exponentially
+    // with age and reaches certainty at max_age.
+    Probabilistic,
+}
+
+// Per-frame chance of death at birth under the probabilistic model. The
+// chance rises by a factor of 1 / GOMPERTZ_BASE_HAZARD over a lifetime.
+const GOMPERTZ_BASE_HAZARD: f64 = 1e-4;
+
+impl MortalityModel {
+    pub fn hazard(&self, age: isize, max_age: isize) -> f64 {
+        if age >= max_age {
+            return 1.0;
+        }
+        match self {
+            MortalityModel::Hard => 0.0,
+            MortalityModel::Probabilistic => {
+                let life_fraction = age.max(0) as f64 / max_age as f64;
+                GOMPERTZ_BASE_HAZARD.powf(1.0 - life_fraction)
+            }
+        }
+    }
+
+    fn dies(&self, age: isize, max_age: isize, rng: &mut impl Rng) -> bool {
+        match self.hazard(age, max_age) {
+            hazard if hazard >= 1.0 => true,
+            hazard if hazard <= 0.0 => false,
+            hazard => rng.gen_bool(hazard),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MovementMode {
+    // Move by the full displacement vector.
+    #[default]
+    Free,
+    // Move at most one cell per frame, along the dominant axis only.
+    Manhattan,
+}
+
+impl MovementMode {
+    pub fn quantize(&self, ds: Vector2<isize>) -> Vector2<isize> {
+        match self {
+            MovementMode::Free => ds,
+            MovementMode::Manhattan if ds.x.abs() >= ds.y.abs() => Vector2::new(ds.x.signum(), 0),
+            MovementMode::Manhattan => Vector2::new(0, ds.y.signum()),
+        }
+    }
+}
+
+impl Default for Attribute {
+    fn default() -> Self {
+        Self::default_attributes()
+    }
 }
 
 impl Attribute {
+    pub fn builder() -> AttributeBuilder {
+        AttributeBuilder::new()
+    }
+
     fn default_attributes() -> Attribute {
         Attribute {
             max_energy: 1000,
@@ -74,184 +674,1639 @@ impl Attribute {
             puberty_age: 100,
             body_states: Vec::new(),
             metabolism: 0.1,
+            movement_cost_factor: 1.0,
+            movement_scale: -2.0,
+            metabolism_exponent: 1.0,
+            min_reproduction_energy: 0,
+            split_fraction: 0.5,
+            senescence_factor: 0.0,
+            transition_cost_factor: 1.0,
+            movement_mode: MovementMode::Free,
+            compactness_factor: 1.0,
+            mortality_model: MortalityModel::Hard,
+            cycle_mode: CycleMode::Forward,
+            max_mutation_rate: 1.0,
+            mutation_rate_regression: 0.0,
+            grid_aligned: false,
+            starting_energy_fraction: 1.0,
+            behavior_mode: BehaviorMode::Cycle,
+            mutation_cost: 0.0,
         }
     }
-}
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct Gene {
-    pub id: isize,
-    pub name: String,
-    pub value: isize,
-    pub attribute_type: AttributeType,
-}
+    // Adds each gene's weighted effect on top of the current values, without
+    // clamping.
+    fn apply_genes<'a>(&mut self, genes: impl IntoIterator<Item = &'a Gene>) {
+        for gene in genes {
+            let weight = gene.weight;
+            let scaled = |value: isize| (value as f32 * weight).round() as isize;
+            match &gene.attribute_type {
+                AttributeType::MaxEnergy(value) => self.max_energy += scaled(*value),
+                AttributeType::MaxAge(value) => self.max_age += scaled(*value),
+                AttributeType::MaxSize(value) => self.max_size += scaled(*value),
+                AttributeType::ReproductionRate(value) => self.reproduction_rate += *value * weight,
+                AttributeType::MutationRate(value) => self.mutation_rate += *value * weight,
+                AttributeType::PubertyAge(value) => self.puberty_age += scaled(*value),
+                AttributeType::BodyStates(value) => {
+                    if weight > 0.0 {
+                        self.body_states.extend(value.iter().cloned())
+                    }
+                }
+                AttributeType::Metabolism(value) => self.metabolism += *value * weight,
+            }
+        }
+    }
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct Genome {
-    pub genes: Vec<Gene>,
+    // Rates feed straight into probability rolls, so they must stay in [0, 1].
+    pub fn clamp_to_valid_ranges(&mut self) {
+        self.max_energy = self.max_energy.max(0);
+        self.max_age = self.max_age.max(0);
+        self.max_size = self.max_size.max(0);
+        self.puberty_age = self.puberty_age.max(0);
+        self.reproduction_rate = self.reproduction_rate.clamp(0.0, 1.0);
+        self.max_mutation_rate = self.max_mutation_rate.clamp(0.0, 1.0);
+        self.mutation_rate = self.mutation_rate.clamp(0.0, self.max_mutation_rate);
+        self.mutation_rate_regression = self.mutation_rate_regression.clamp(0.0, 1.0);
+        self.metabolism = self.metabolism.clamp(0.0, 1.0);
+        self.movement_cost_factor = self.movement_cost_factor.max(0.0);
+        self.split_fraction = self.split_fraction.clamp(0.0, 1.0);
+        self.starting_energy_fraction = self.starting_energy_fraction.clamp(0.0, 1.0);
+        self.senescence_factor = self.senescence_factor.max(0.0);
+        self.transition_cost_factor = self.transition_cost_factor.max(0.0);
+        self.compactness_factor = self.compactness_factor.max(0.0);
+        self.mutation_cost = self.mutation_cost.max(0.0);
+    }
 }
 
-pub struct Organism {
-    pub id: isize,
-    pub genome: Genome,
-    pub energy: isize,
-    pub age: isize,
-    pub location: Vector2<isize>,
-    pub body_squares: Body,
-    pub current_body_state: isize,
-    pub attributes: Attribute,
+#[derive(Debug, PartialEq, Clone)]
+pub struct AttributeBuilder {
+    attribute: Attribute,
 }
 
-pub enum OrganismState {
-    Alive,
-    Dead,
+impl Default for AttributeBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl Organism {
-    pub fn apply_gene_effects(&mut self) {
-        for gene in &self.genome.genes {
-            match &gene.attribute_type {
-                AttributeType::MaxEnergy(value) => self.attributes.max_energy += *value,
-                AttributeType::MaxAge(value) => self.attributes.max_age += *value,
-                AttributeType::MaxSize(value) => self.attributes.max_size += *value,
-                AttributeType::ReproductionRate(value) => {
-                    self.attributes.reproduction_rate += *value
-                }
-                AttributeType::MutationRate(value) => self.attributes.mutation_rate += *value,
-                AttributeType::PubertyAge(value) => self.attributes.puberty_age += *value,
-                AttributeType::BodyStates(value) => {
-                    self.attributes.body_states.extend(value.iter().cloned())
-                }
-                AttributeType::Metabolism(value) => self.attributes.metabolism += *value,
-            }
+// Starts from the simulation defaults, so only the overridden fields differ.
+impl AttributeBuilder {
+    pub fn new() -> Self {
+        Self {
+            attribute: Attribute::default_attributes(),
         }
     }
 
-    fn calculate_movement(current_body: &Body, new_body: &Body) -> Vector2<isize> {
-        let mut movement = Vector2::zeros();
+    pub fn max_energy(mut self, max_energy: isize) -> Self {
+        self.attribute.max_energy = max_energy;
+        self
+    }
 
-        // Calculate the average direction of change in controlled squares
-        for (current_sq, new_sq) in current_body.squares.iter().zip(new_body.squares.iter()) {
-            let delta = new_sq.position - current_sq.position;
-            movement += Vector2::new(delta.x as isize, delta.y as isize);
-        }
+    pub fn max_age(mut self, max_age: isize) -> Self {
+        self.attribute.max_age = max_age;
+        self
+    }
 
-        // Adjust movement based on the difference between controlled squares
-        let scaling_factor = -2; // Adjust this to control movement strength
-        movement *= scaling_factor;
+    pub fn max_size(mut self, max_size: isize) -> Self {
+        self.attribute.max_size = max_size;
+        self
+    }
 
-        movement
+    pub fn reproduction_rate(mut self, reproduction_rate: f32) -> Self {
+        self.attribute.reproduction_rate = reproduction_rate;
+        self
     }
 
-    fn mutate(&mut self) {
-        let mut rng = rand::thread_rng();
-        let mut new_body_states: Vec<Body> = Vec::new();
-        for body in &self.attributes.body_states {
-            let mut new_body = Body::new();
-            for square in &body.squares {
-                let mut new_square = square.clone();
-                let x = new_square.position.x as f64;
-                let y = new_square.position.y as f64;
-                let x = x + rng.gen_range(-1.0..1.0);
-                let y = y + rng.gen_range(-1.0..1.0);
-                new_square.position = Vector2::new(x, y);
-                new_body.add_square(new_square);
-            }
-            new_body_states.push(new_body);
-        }
-        self.attributes.body_states = new_body_states;
+    pub fn mutation_rate(mut self, mutation_rate: f32) -> Self {
+        self.attribute.mutation_rate = mutation_rate;
+        self
     }
 
-    pub fn new(id: isize, genome: Genome) -> Self {
-        let attributes = Attribute::default_attributes();
-        let body = attributes.body_states[0].clone();
-        let mut organism = Self {
-            id,
-            genome,
-            energy: 1000,
-            age: 0,
-            location: Vector2::new(0, 0),
-            body_squares: body,
-            current_body_state: 0,
-            attributes,
-        };
-        organism.apply_gene_effects();
-        organism
+    pub fn puberty_age(mut self, puberty_age: isize) -> Self {
+        self.attribute.puberty_age = puberty_age;
+        self
     }
 
-    pub fn reproduce(&self) -> Organism {
-        let location_offset_x = rand::thread_rng().gen_range(-1..1);
-        let location_offset_y = rand::thread_rng().gen_range(-1..1);
-        let location_offset = Vector2::new(location_offset_x, location_offset_y);
-        let mut offspring = Self {
-            id: self.id,
-            genome: self.genome.clone(),
-            energy: self.energy / 2,
-            age: 0,
-            location: self.location + location_offset,
-            body_squares: self.body_squares.clone(),
-            current_body_state: 0,
-            attributes: self.attributes.clone(),
-        };
+    pub fn metabolism(mut self, metabolism: f32) -> Self {
+        self.attribute.metabolism = metabolism;
+        self
+    }
 
-        offspring.mutate();
-        offspring
+    pub fn movement_cost_factor(mut self, movement_cost_factor: f32) -> Self {
+        self.attribute.movement_cost_factor = movement_cost_factor;
+        self
     }
 
-    pub fn next_frame(&mut self) -> (OrganismState, Option<Organism>) {
-        let prev_body = &self.body_squares;
-        let next_body = match &self
-            .attributes
-            .body_states
-            .get(self.current_body_state as usize)
-        {
-            Some(body) => *body,
-            None => &self.attributes.body_states[0],
-        };
-        let ds = Self::calculate_movement(prev_body, next_body);
-        self.location += Vector2::new(ds.x as isize, ds.y as isize);
-        if self.current_body_state < self.attributes.body_states.len() as isize - 1 {
-            self.current_body_state += 1;
-        } else {
-            self.current_body_state = 0;
-        }
-        self.energy -=
-            (self.attributes.metabolism * self.body_squares.squares.len() as f32) as isize;
+    pub fn movement_scale(mut self, movement_scale: f32) -> Self {
+        self.attribute.movement_scale = movement_scale;
+        self
+    }
 
-        if self.energy <= 0 {
-            return (OrganismState::Dead, None);
-        }
+    pub fn metabolism_exponent(mut self, metabolism_exponent: f32) -> Self {
+        self.attribute.metabolism_exponent = metabolism_exponent;
+        self
+    }
 
-        self.age += 1;
-        if self.age >= self.attributes.max_age {
-            return (OrganismState::Dead, None);
-        }
-        let will_mutate = rand::thread_rng().gen_range(0.0..1.0) < self.attributes.mutation_rate;
-        if will_mutate {
-            self.mutate();
-        }
-        let will_reproduce =
-            rand::thread_rng().gen_range(0.0..1.0) < self.attributes.reproduction_rate;
+    pub fn min_reproduction_energy(mut self, min_reproduction_energy: isize) -> Self {
+        self.attribute.min_reproduction_energy = min_reproduction_energy;
+        self
+    }
 
-        let mut abort = false;
-        let offspring = self.reproduce();
-        if will_reproduce {
-            self.energy -= offspring.energy;
+    pub fn split_fraction(mut self, split_fraction: f32) -> Self {
+        self.attribute.split_fraction = split_fraction;
+        self
+    }
 
-            for body in &offspring.attributes.body_states {
-                if !self.body_squares.check_blueprint_validity(&body.squares) {
-                    abort = true;
-                    break;
-                }
-            }
-        }
+    pub fn senescence_factor(mut self, senescence_factor: f32) -> Self {
+        self.attribute.senescence_factor = senescence_factor;
+        self
+    }
 
-        if abort {
-            (OrganismState::Alive, None)
-        } else if will_reproduce {
-            (OrganismState::Alive, Some(offspring))
-        } else {
-            (OrganismState::Alive, None)
+    pub fn transition_cost_factor(mut self, transition_cost_factor: f32) -> Self {
+        self.attribute.transition_cost_factor = transition_cost_factor;
+        self
+    }
+
+    pub fn movement_mode(mut self, movement_mode: MovementMode) -> Self {
+        self.attribute.movement_mode = movement_mode;
+        self
+    }
+
+    pub fn compactness_factor(mut self, compactness_factor: f32) -> Self {
+        self.attribute.compactness_factor = compactness_factor;
+        self
+    }
+
+    pub fn mortality_model(mut self, mortality_model: MortalityModel) -> Self {
+        self.attribute.mortality_model = mortality_model;
+        self
+    }
+
+    pub fn cycle_mode(mut self, cycle_mode: CycleMode) -> Self {
+        self.attribute.cycle_mode = cycle_mode;
+        self
+    }
+
+    pub fn max_mutation_rate(mut self, max_mutation_rate: f32) -> Self {
+        self.attribute.max_mutation_rate = max_mutation_rate;
+        self
+    }
+
+    pub fn mutation_rate_regression(mut self, mutation_rate_regression: f32) -> Self {
+        self.attribute.mutation_rate_regression = mutation_rate_regression;
+        self
+    }
+
+    pub fn grid_aligned(mut self, grid_aligned: bool) -> Self {
+        self.attribute.grid_aligned = grid_aligned;
+        self
+    }
+
+    pub fn starting_energy_fraction(mut self, starting_energy_fraction: f32) -> Self {
+        self.attribute.starting_energy_fraction = starting_energy_fraction;
+        self
+    }
+
+    pub fn behavior_mode(mut self, behavior_mode: BehaviorMode) -> Self {
+        self.attribute.behavior_mode = behavior_mode;
+        self
+    }
+
+    pub fn mutation_cost(mut self, mutation_cost: f32) -> Self {
+        self.attribute.mutation_cost = mutation_cost;
+        self
+    }
+
+    pub fn with_body_state(mut self, body: Body) -> Self {
+        self.attribute.body_states.push(body);
+        self
+    }
+
+    pub fn body_states(mut self, body_states: Vec<Body>) -> Self {
+        self.attribute.body_states = body_states;
+        self
+    }
+
+    pub fn build(self) -> Attribute {
+        self.attribute
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Gene {
+    pub id: isize,
+    pub name: String,
+    pub value: isize,
+    pub attribute_type: AttributeType,
+    #[cfg_attr(feature = "serde", serde(default = "default_gene_weight"))]
+    pub weight: f32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub expression_condition: ExpressionCondition,
+}
+
+// When a gene takes effect, judged against the organism's current energy.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ExpressionCondition {
+    #[default]
+    Always,
+    EnergyAbove(isize),
+    EnergyBelow(isize),
+}
+
+impl ExpressionCondition {
+    pub fn is_met(&self, energy: isize) -> bool {
+        match *self {
+            ExpressionCondition::Always => true,
+            ExpressionCondition::EnergyAbove(threshold) => energy > threshold,
+            ExpressionCondition::EnergyBelow(threshold) => energy < threshold,
+        }
+    }
+}
+
+pub const DEFAULT_GENE_WEIGHT: f32 = 1.0;
+
+#[cfg(feature = "serde")]
+fn default_gene_weight() -> f32 {
+    DEFAULT_GENE_WEIGHT
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Genome {
+    pub genes: Vec<Gene>,
+}
+
+impl Genome {
+    pub fn builder() -> GenomeBuilder {
+        GenomeBuilder::new()
+    }
+
+    // A random genome for bootstrapping populations. Genes shift the default
+    // attributes, so the ranges below keep every attribute sane: positive
+    // energy and lifespan, and bodies of two to six squares that stay under
+    // max_size.
+    pub fn random(rng: &mut impl Rng) -> Genome {
+        Self::random_from_template(&Attribute::default_attributes(), rng)
+    }
+
+    // Like random, for organisms grown from `template`. The max_size gene is
+    // picked so that max_size ends up between 6 and 10 whatever the
+    // template's own value.
+    pub fn random_from_template(template: &Attribute, rng: &mut impl Rng) -> Genome {
+        let square_count = rng.gen_range(2..=6);
+        let body_states = (0..rng.gen_range(1..=3))
+            .map(|_| Body::random(square_count, rng))
+            .collect();
+        Genome::builder()
+            .max_energy(rng.gen_range(-500..=1000))
+            .max_age(rng.gen_range(-500..=500))
+            .max_size(rng.gen_range(6..=10) - template.max_size)
+            .reproduction_rate(rng.gen_range(-0.05..=0.1))
+            .mutation_rate(rng.gen_range(-0.05..=0.1))
+            .puberty_age(rng.gen_range(-50..=50))
+            .metabolism(rng.gen_range(-0.05..=0.2))
+            .body_states(body_states)
+            .build()
+            .expect("random genomes carry one gene per attribute")
+    }
+
+    // Collects every problem with the genome rather than stopping at the
+    // first. Attribute ranges are checked on the values the genes would give
+    // an organism before clamping.
+    pub fn validate(&self) -> Result<(), Vec<GenomeError>> {
+        let mut errors = Vec::new();
+        for (index, gene) in self.genes.iter().enumerate() {
+            if self.genes[..index]
+                .iter()
+                .any(|earlier| earlier.id == gene.id)
+                && !errors.contains(&GenomeError::DuplicateGeneId(gene.id))
+            {
+                errors.push(GenomeError::DuplicateGeneId(gene.id));
+            }
+            if let AttributeType::BodyStates(bodies) = &gene.attribute_type {
+                if bodies.iter().any(|body| body.size() == 0) {
+                    errors.push(GenomeError::EmptyBody { gene: gene.id });
+                }
+            }
+        }
+
+        // Conditional genes are checked as if they were all expressed.
+        let mut attributes = Attribute::default_attributes();
+        attributes.apply_genes(&self.genes);
+        if attributes.body_states.is_empty() {
+            errors.push(GenomeError::MissingBodyStates);
+        }
+        let out_of_range = [
+            ("max_energy", attributes.max_energy <= 0),
+            ("max_age", attributes.max_age <= 0),
+            ("max_size", attributes.max_size <= 0),
+            ("puberty_age", attributes.puberty_age < 0),
+            (
+                "reproduction_rate",
+                !(0.0..=1.0).contains(&attributes.reproduction_rate),
+            ),
+            (
+                "mutation_rate",
+                !(0.0..=1.0).contains(&attributes.mutation_rate),
+            ),
+            ("metabolism", !(0.0..=1.0).contains(&attributes.metabolism)),
+        ];
+        for (attribute, invalid) in out_of_range {
+            if invalid {
+                errors.push(GenomeError::OutOfRange { attribute });
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    // A copy of the genome without the gene carrying `id`.
+    pub fn without_gene(&self, id: isize) -> Genome {
+        Genome {
+            genes: self
+                .genes
+                .iter()
+                .filter(|gene| gene.id != id)
+                .cloned()
+                .collect(),
+        }
+    }
+
+    // A copy of the genome with the gene carrying `id` set to `value`, payload
+    // included. Only integer attributes can take an integer value; for rate,
+    // metabolism and body state genes this is an error, as changing just the
+    // value field would not change the phenotype.
+    pub fn with_gene_value(&self, id: isize, value: isize) -> Result<Genome, GenomeError> {
+        let mut genome = self.clone();
+        for gene in genome.genes.iter_mut().filter(|gene| gene.id == id) {
+            match &mut gene.attribute_type {
+                AttributeType::MaxEnergy(payload)
+                | AttributeType::MaxAge(payload)
+                | AttributeType::MaxSize(payload)
+                | AttributeType::PubertyAge(payload) => *payload = value,
+                other => {
+                    return Err(GenomeError::NotAnIntegerGene {
+                        gene: id,
+                        attribute: other.name(),
+                    })
+                }
+            }
+            gene.value = value;
+        }
+        Ok(genome)
+    }
+
+    // Sum of per-gene differences, with genes matched by id. Matching genes
+    // contribute a normalized difference between 0 and 1; a gene carried by
+    // only one genome counts as a full difference of 1.
+    pub fn distance(&self, other: &Genome) -> f64 {
+        let mut distance = 0.0;
+        for gene in &self.genes {
+            distance += match other.genes.iter().find(|candidate| candidate.id == gene.id) {
+                Some(partner) => gene.attribute_type.difference(&partner.attribute_type),
+                None => 1.0,
+            };
+        }
+        let unmatched = other
+            .genes
+            .iter()
+            .filter(|gene| !self.genes.iter().any(|candidate| candidate.id == gene.id))
+            .count();
+        distance + unmatched as f64
+    }
+
+    // Genes are matched by id. Shared genes come from either parent at random,
+    // except that float-valued attributes of the same kind are averaged; genes
+    // only one parent carries are inherited unchanged.
+    pub fn crossover(&self, other: &Genome, rng: &mut impl Rng) -> Genome {
+        let mut genes = Vec::with_capacity(self.genes.len().max(other.genes.len()));
+        for gene in &self.genes {
+            match other.genes.iter().find(|candidate| candidate.id == gene.id) {
+                Some(partner) => genes.push(gene.cross_with(partner, rng)),
+                None => genes.push(gene.clone()),
+            }
+        }
+        for gene in &other.genes {
+            if !self.genes.iter().any(|candidate| candidate.id == gene.id) {
+                genes.push(gene.clone());
+            }
+        }
+        Genome { genes }
+    }
+
+    // Interpolates genes matched by id, so t = 0 gives this genome and t = 1
+    // gives `other`. Everything else, including genes only one side carries,
+    // comes from whichever end `t` is closer to.
+    pub fn lerp(&self, other: &Genome, t: f32) -> Genome {
+        let (nearer, farther) = if t < 0.5 {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let genes = nearer
+            .genes
+            .iter()
+            .map(|gene| {
+                match farther
+                    .genes
+                    .iter()
+                    .find(|candidate| candidate.id == gene.id)
+                {
+                    Some(partner) if t < 0.5 => gene.lerp(partner, t),
+                    Some(partner) => partner.lerp(gene, t),
+                    None => gene.clone(),
+                }
+            })
+            .collect();
+        Genome { genes }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct GenomeBuilder {
+    genes: Vec<Gene>,
+    allow_duplicates: bool,
+}
+
+// Gene ids are handed out in insertion order, starting at 0.
+impl GenomeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn allow_duplicates(mut self) -> Self {
+        self.allow_duplicates = true;
+        self
+    }
+
+    pub fn gene(mut self, attribute_type: AttributeType) -> Self {
+        let value = match attribute_type {
+            AttributeType::MaxEnergy(value)
+            | AttributeType::MaxAge(value)
+            | AttributeType::MaxSize(value)
+            | AttributeType::PubertyAge(value) => value,
+            _ => 0,
+        };
+        self.genes.push(Gene {
+            id: self.genes.len() as isize,
+            name: attribute_type.name().to_string(),
+            value,
+            attribute_type,
+            weight: DEFAULT_GENE_WEIGHT,
+            expression_condition: ExpressionCondition::Always,
+        });
+        self
+    }
+
+    // Makes the most recently added gene conditional.
+    pub fn expressed_when(mut self, condition: ExpressionCondition) -> Self {
+        if let Some(gene) = self.genes.last_mut() {
+            gene.expression_condition = condition;
+        }
+        self
+    }
+
+    pub fn max_energy(self, value: isize) -> Self {
+        self.gene(AttributeType::MaxEnergy(value))
+    }
+
+    pub fn max_age(self, value: isize) -> Self {
+        self.gene(AttributeType::MaxAge(value))
+    }
+
+    pub fn max_size(self, value: isize) -> Self {
+        self.gene(AttributeType::MaxSize(value))
+    }
+
+    pub fn reproduction_rate(self, value: f32) -> Self {
+        self.gene(AttributeType::ReproductionRate(value))
+    }
+
+    pub fn mutation_rate(self, value: f32) -> Self {
+        self.gene(AttributeType::MutationRate(value))
+    }
+
+    pub fn puberty_age(self, value: isize) -> Self {
+        self.gene(AttributeType::PubertyAge(value))
+    }
+
+    pub fn metabolism(self, value: f32) -> Self {
+        self.gene(AttributeType::Metabolism(value))
+    }
+
+    pub fn body_state(self, body: Body) -> Self {
+        self.body_states(vec![body])
+    }
+
+    pub fn body_states(self, bodies: Vec<Body>) -> Self {
+        self.gene(AttributeType::BodyStates(bodies))
+    }
+
+    pub fn build(self) -> Result<Genome, GenomeError> {
+        if !self.allow_duplicates {
+            for (index, gene) in self.genes.iter().enumerate() {
+                let kind = std::mem::discriminant(&gene.attribute_type);
+                if self.genes[..index]
+                    .iter()
+                    .any(|earlier| std::mem::discriminant(&earlier.attribute_type) == kind)
+                {
+                    return Err(GenomeError::DuplicateAttribute(gene.attribute_type.name()));
+                }
+            }
+        }
+        Ok(Genome { genes: self.genes })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum GenomeError {
+    DuplicateAttribute(&'static str),
+    DuplicateGeneId(isize),
+    MissingBodyStates,
+    EmptyBody {
+        gene: isize,
+    },
+    OutOfRange {
+        attribute: &'static str,
+    },
+    NotAnIntegerGene {
+        gene: isize,
+        attribute: &'static str,
+    },
+}
+
+impl fmt::Display for GenomeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenomeError::DuplicateAttribute(name) => {
+                write!(f, "genome has more than one {} gene", name)
+            }
+            GenomeError::DuplicateGeneId(id) => {
+                write!(f, "genome has more than one gene with id {}", id)
+            }
+            GenomeError::MissingBodyStates => write!(f, "genome has no body states"),
+            GenomeError::EmptyBody { gene } => {
+                write!(f, "gene {} has a body state without squares", gene)
+            }
+            GenomeError::OutOfRange { attribute } => {
+                write!(f, "genome gives {} a value out of range", attribute)
+            }
+            GenomeError::NotAnIntegerGene { gene, attribute } => {
+                write!(
+                    f,
+                    "gene {} is a {} gene and takes no integer value",
+                    gene, attribute
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for GenomeError {}
+
+// Rates drift by up to mutation_rate * FLOAT_GENE_DRIFT_SCALE, so the default
+// rate of 0.1 moves them by at most 0.01.
+const FLOAT_GENE_DRIFT_SCALE: f32 = 0.1;
+// Squares move by up to mutation_rate * BODY_JITTER_SCALE on each axis, so a
+// rate of 0.5 gives the full one-cell jitter and a rate of 0 leaves them put.
+const BODY_JITTER_SCALE: f64 = 2.0;
+// Chance, relative to the mutation rate, that a body state is rotated.
+const ROTATION_CHANCE: f64 = 0.1;
+// Chance, relative to the mutation rate, that a body state is mirrored.
+const REFLECTION_CHANCE: f64 = 0.1;
+
+impl Gene {
+    // Nudges the gene by a small random amount: integers by 1 to 3, rates by an
+    // amount that grows with `mutation_rate`. Body states are shaped by body
+    // mutation instead.
+    pub(crate) fn perturb(&mut self, mutation_rate: f32, rng: &mut impl Rng) {
+        let sign = if rng.gen_bool(0.5) { 1 } else { -1 };
+        let step = sign * rng.gen_range(1..=3);
+        self.value += step;
+        let max_drift = mutation_rate.max(0.0) * FLOAT_GENE_DRIFT_SCALE;
+        let drift = if max_drift > 0.0 {
+            rng.gen_range(-max_drift..max_drift)
+        } else {
+            0.0
+        };
+        match &mut self.attribute_type {
+            AttributeType::MaxEnergy(value)
+            | AttributeType::MaxAge(value)
+            | AttributeType::MaxSize(value)
+            | AttributeType::PubertyAge(value) => *value += step,
+            AttributeType::ReproductionRate(rate)
+            | AttributeType::MutationRate(rate)
+            | AttributeType::Metabolism(rate) => *rate = (*rate + drift).clamp(-1.0, 1.0),
+            AttributeType::BodyStates(_) => {}
+        }
+    }
+
+    fn lerp(&self, other: &Gene, t: f32) -> Gene {
+        let nearer = if t < 0.5 { self } else { other };
+        Gene {
+            value: lerp_whole(self.value, other.value, t),
+            weight: lerp(self.weight, other.weight, t),
+            attribute_type: self
+                .attribute_type
+                .lerp(&other.attribute_type, t)
+                .unwrap_or_else(|| nearer.attribute_type.clone()),
+            ..nearer.clone()
+        }
+    }
+
+    fn cross_with(&self, other: &Gene, rng: &mut impl Rng) -> Gene {
+        let mut child = if rng.gen_bool(0.5) {
+            self.clone()
+        } else {
+            other.clone()
+        };
+        let averaged = match (&self.attribute_type, &other.attribute_type) {
+            (AttributeType::ReproductionRate(a), AttributeType::ReproductionRate(b)) => {
+                Some(AttributeType::ReproductionRate((a + b) / 2.0))
+            }
+            (AttributeType::MutationRate(a), AttributeType::MutationRate(b)) => {
+                Some(AttributeType::MutationRate((a + b) / 2.0))
+            }
+            (AttributeType::Metabolism(a), AttributeType::Metabolism(b)) => {
+                Some(AttributeType::Metabolism((a + b) / 2.0))
+            }
+            _ => None,
+        };
+        if let Some(attribute_type) = averaged {
+            child.attribute_type = attribute_type;
+        }
+        child
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+// Fields are only writable inside the crate, so outside code goes through
+// the accessors and the checked mutators below.
+pub struct Organism {
+    pub(crate) id: isize,
+    pub(crate) genome: Genome,
+    pub(crate) energy: isize,
+    pub(crate) age: isize,
+    pub(crate) location: Vector2<isize>,
+    pub(crate) body_squares: Body,
+    pub(crate) current_body_state: isize,
+    // Set while a PingPong cycle is walking back towards the first state.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) body_state_reversed: bool,
+    pub(crate) attributes: Attribute,
+    // The attributes genes were applied on top of, kept so mutation can
+    // re-derive from the same baseline.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) base_attributes: Attribute,
+    // While set, the organism grows one square per frame towards this body
+    // instead of cycling through its body states.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) target_body: Option<Body>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) parent_id: Option<isize>,
+    // The organism's own random stream, so stepping does not depend on the
+    // order organisms are scheduled in.
+    #[cfg_attr(feature = "serde", serde(default = "unseeded_rng"))]
+    pub(crate) rng: Pcg64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) behavior_state: BehaviorState,
+}
+
+// A stream that depends only on the world seed and the organism id.
+fn seeded_rng(seed: u64, id: isize) -> Pcg64 {
+    Pcg64::new(seed as u128, id as u128)
+}
+
+#[cfg(feature = "serde")]
+fn unseeded_rng() -> Pcg64 {
+    seeded_rng(0, 0)
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum OrganismState {
+    Alive,
+    Dead(DeathCause),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DeathCause {
+    Starvation,
+    OldAge,
+    Predation,
+    OutOfBounds,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum OrganismError {
+    EmptyBodyStates,
+    BodyStateOutOfRange { index: isize, len: usize },
+    OutOfBounds { location: Vector2<isize> },
+}
+
+impl fmt::Display for OrganismError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrganismError::EmptyBodyStates => {
+                write!(f, "genome produced no body states for the organism")
+            }
+            OrganismError::BodyStateOutOfRange { index, len } => write!(
+                f,
+                "body state {} is out of range for {} body states",
+                index, len
+            ),
+            OrganismError::OutOfBounds { location } => write!(
+                f,
+                "location ({}, {}) is outside the world",
+                location.x, location.y
+            ),
+        }
+    }
+}
+
+impl std::error::Error for OrganismError {}
+
+// Why an organism could not produce offspring.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ReproductionError {
+    // The parent is at or below min_reproduction_energy, or too low to hand
+    // the child any energy at all.
+    InsufficientEnergy { energy: isize },
+    // The mutated child has a body state over max_size or one the parent's
+    // body could not produce.
+    InvalidBlueprint,
+    // Nowhere around the parent is free for the child.
+    NoValidPlacement,
+}
+
+impl fmt::Display for ReproductionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReproductionError::InsufficientEnergy { energy } => {
+                write!(f, "{} energy is not enough to reproduce", energy)
+            }
+            ReproductionError::InvalidBlueprint => {
+                write!(f, "offspring body states are not valid")
+            }
+            ReproductionError::NoValidPlacement => write!(f, "no free cell for offspring"),
+        }
+    }
+}
+
+impl std::error::Error for ReproductionError {}
+
+impl Organism {
+    // Each gene contributes its payload scaled by its weight, so competing
+    // genes for one attribute blend; a gene of weight 0 has no effect. Genes
+    // whose expression condition fails at the current energy are skipped.
+    pub fn apply_gene_effects(&mut self) {
+        let energy = self.energy;
+        self.attributes.apply_genes(
+            self.genome
+                .genes
+                .iter()
+                .filter(|gene| gene.expression_condition.is_met(energy)),
+        );
+        self.attributes.clamp_to_valid_ranges();
+    }
+
+    fn calculate_movement(current_body: &Body, new_body: &Body, scale: f32) -> Vector2<isize> {
+        // Bodies are compared through their centroids rather than square by
+        // square, so states with different square counts still line up. The
+        // shift is weighted by the new body's square count, which matches the
+        // old per-square sum whenever both states have the same squares.
+        let (Some(current_center), Some(new_center)) =
+            (current_body.center_of_mass(), new_body.center_of_mass())
+        else {
+            return Vector2::zeros();
+        };
+        let delta = (new_center - current_center) * new_body.size() as f64;
+
+        // Negative scales push the organism away from the direction its
+        // squares moved in.
+        let delta = delta * scale as f64;
+        Vector2::new(delta.x as isize, delta.y as isize)
+    }
+
+    // `body` with every square nudged by up to the mutation rate's jitter on
+    // each axis.
+    pub(crate) fn jitter_body(&self, body: &Body, rng: &mut impl Rng) -> Body {
+        let jitter = self.attributes.mutation_rate.max(0.0) as f64 * BODY_JITTER_SCALE;
+        let mut new_body = Body::new().with_adjacency_threshold(body.adjacency_threshold);
+        for square in &body.squares {
+            let mut new_square = *square;
+            let x = new_square.position.x;
+            let y = new_square.position.y;
+            let x = x + rng.gen_range(-1.0..1.0) * jitter;
+            let y = y + rng.gen_range(-1.0..1.0) * jitter;
+            new_square.position = Vector2::new(x, y);
+            new_body.add_square(new_square);
+        }
+        // On the grid, jitter only matters once it carries a square past the
+        // midpoint to a neighbouring cell.
+        if self.attributes.grid_aligned {
+            new_body.snap_to_grid();
+        }
+        // Jitter that tears the body apart or stacks squares is discarded.
+        if !new_body.is_connected() || new_body.has_overlaps() {
+            return body.clone();
+        }
+        new_body
+    }
+
+    // The built-in mutation, run by DefaultMutator.
+    pub(crate) fn mutate(&mut self, rng: &mut impl Rng) {
+        let mut new_body_states: Vec<Body> = Vec::new();
+        for body in &self.attributes.body_states {
+            let mut new_body = self.jitter_body(body, rng);
+            let max_size = self.attributes.max_size.max(1) as usize;
+            if rng.gen_bool((self.attributes.mutation_rate as f64).clamp(0.0, 1.0)) {
+                if !rng.gen_bool(0.5) {
+                    new_body.shrink(rng);
+                } else if new_body.size() < max_size {
+                    new_body.grow(rng);
+                }
+            }
+            let rotation_chance = self.attributes.mutation_rate.max(0.0) as f64 * ROTATION_CHANCE;
+            if rng.gen_bool(rotation_chance.clamp(0.0, 1.0)) {
+                new_body = new_body.rotate(rng.gen_range(1..=3));
+            }
+            let reflection_chance =
+                self.attributes.mutation_rate.max(0.0) as f64 * REFLECTION_CHANCE;
+            if rng.gen_bool(reflection_chance.clamp(0.0, 1.0)) {
+                new_body = if rng.gen_bool(0.5) {
+                    new_body.reflect_x()
+                } else {
+                    new_body.reflect_y()
+                };
+            }
+            // Bodies already over the cap are trimmed back down to it.
+            while new_body.size() > max_size && new_body.shrink(rng) {}
+            new_body.canonicalize();
+            new_body_states.push(new_body);
+        }
+        self.attributes.body_states = new_body_states;
+
+        let mut genome_changed = false;
+        let mutation_rate = self.attributes.mutation_rate;
+        for gene in &mut self.genome.genes {
+            if rng.gen_bool((mutation_rate as f64).clamp(0.0, 1.0)) {
+                gene.perturb(mutation_rate, rng);
+                genome_changed = true;
+            }
+        }
+        // Mutation rate genes shift the default rate, so shrinking them pulls
+        // the rate back towards the default baseline.
+        let regression = self.attributes.mutation_rate_regression;
+        if regression > 0.0 {
+            for gene in &mut self.genome.genes {
+                if let AttributeType::MutationRate(value) = &mut gene.attribute_type {
+                    *value *= 1.0 - regression;
+                    genome_changed = true;
+                }
+            }
+        }
+        if genome_changed {
+            self.rederive_attributes();
+        }
+    }
+
+    // Recomputes the attributes genes control from the baseline and the
+    // current genome. Body states are kept, as they carry the shape changes
+    // made by mutation, and so are the tuning knobs no gene touches.
+    pub(crate) fn rederive_attributes(&mut self) {
+        let body_states = std::mem::take(&mut self.attributes.body_states);
+        let defaults = &self.base_attributes;
+        self.attributes = Attribute {
+            max_energy: defaults.max_energy,
+            max_age: defaults.max_age,
+            max_size: defaults.max_size,
+            reproduction_rate: defaults.reproduction_rate,
+            mutation_rate: defaults.mutation_rate,
+            puberty_age: defaults.puberty_age,
+            metabolism: defaults.metabolism,
+            ..self.attributes.clone()
+        };
+        self.apply_gene_effects();
+        self.attributes.body_states = body_states;
+        // A gene change may have lowered the cap below what is already stored.
+        self.energy = self.energy.min(self.attributes.max_energy);
+    }
+
+    pub fn new(id: isize, genome: Genome) -> Result<Self, OrganismError> {
+        Self::from_template(id, genome, &Attribute::default_attributes())
+    }
+
+    // Like new, but with genes applied on top of `template` instead of the
+    // built-in default attributes.
+    pub fn from_template(
+        id: isize,
+        genome: Genome,
+        template: &Attribute,
+    ) -> Result<Self, OrganismError> {
+        let mut organism = Self {
+            id,
+            genome,
+            energy: 0,
+            age: 0,
+            location: Vector2::new(0, 0),
+            body_squares: Body::new(),
+            current_body_state: 0,
+            body_state_reversed: false,
+            attributes: template.clone(),
+            base_attributes: template.clone(),
+            target_body: None,
+            parent_id: None,
+            rng: seeded_rng(0, id),
+            behavior_state: BehaviorState::default(),
+        };
+        // The body comes from the genome, so genes have to be applied first.
+        // Conditional genes wait until the starting energy is known, as they
+        // would otherwise be judged against an energy of 0.
+        organism.attributes.apply_genes(
+            organism
+                .genome
+                .genes
+                .iter()
+                .filter(|gene| gene.expression_condition == ExpressionCondition::Always),
+        );
+        organism.attributes.clamp_to_valid_ranges();
+        organism.body_squares = organism
+            .attributes
+            .body_states
+            .first()
+            .cloned()
+            .ok_or(OrganismError::EmptyBodyStates)?;
+        // Energy is set once max_energy is known, so nobody starts over the cap.
+        let starting_energy = organism.attributes.max_energy as f64
+            * organism.attributes.starting_energy_fraction as f64;
+        organism.gain_energy(starting_energy as isize);
+        organism.rederive_attributes();
+        Ok(organism)
+    }
+
+    pub fn id(&self) -> isize {
+        self.id
+    }
+
+    pub fn genome(&self) -> &Genome {
+        &self.genome
+    }
+
+    pub fn energy(&self) -> isize {
+        self.energy
+    }
+
+    pub fn age(&self) -> isize {
+        self.age
+    }
+
+    pub fn location(&self) -> Vector2<isize> {
+        self.location
+    }
+
+    pub fn body_squares(&self) -> &Body {
+        &self.body_squares
+    }
+
+    pub fn current_body_state(&self) -> isize {
+        self.current_body_state
+    }
+
+    pub fn attributes(&self) -> &Attribute {
+        &self.attributes
+    }
+
+    // Attributes are tuning knobs rather than state, so they stay editable.
+    // `f` runs on the baseline as well, so the edit survives re-deriving the
+    // attributes genes control and is passed on to offspring. Those attributes
+    // are then re-derived from the new baseline, everything is clamped back
+    // into range, and energy is cut down to a lowered max_energy.
+    pub fn update_attributes(&mut self, mut f: impl FnMut(&mut Attribute)) {
+        f(&mut self.base_attributes);
+        f(&mut self.attributes);
+        self.base_attributes.clamp_to_valid_ranges();
+        self.rederive_attributes();
+    }
+
+    pub fn base_attributes(&self) -> &Attribute {
+        &self.base_attributes
+    }
+
+    pub fn target_body(&self) -> Option<&Body> {
+        self.target_body.as_ref()
+    }
+
+    pub fn set_target_body(&mut self, target_body: Option<Body>) {
+        self.target_body = target_body;
+    }
+
+    pub fn parent_id(&self) -> Option<isize> {
+        self.parent_id
+    }
+
+    pub fn behavior_state(&self) -> &BehaviorState {
+        &self.behavior_state
+    }
+
+    // Restarts the organism's random stream from `seed` and its id.
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = seeded_rng(seed, self.id);
+    }
+
+    // Moves the organism, refusing locations outside a world of `size`.
+    pub fn set_location(
+        &mut self,
+        location: Vector2<isize>,
+        size: Vector2<usize>,
+    ) -> Result<(), OrganismError> {
+        if field::cell_index(size, (location.x, location.y)).is_none() {
+            return Err(OrganismError::OutOfBounds { location });
+        }
+        self.location = location;
+        Ok(())
+    }
+
+    // A square covers one unit from its position, so a square that is not
+    // aligned to the grid straddles up to four world cells.
+    pub fn occupied_cells(&self) -> Vec<(isize, isize)> {
+        let mut cells = Vec::with_capacity(self.body_squares.squares.len());
+        for square in &self.body_squares.squares {
+            let min_x = self.location.x as f64 + square.position.x;
+            let min_y = self.location.y as f64 + square.position.y;
+            let (first_x, last_x) = (min_x.floor() as isize, (min_x + 1.0).ceil() as isize - 1);
+            let (first_y, last_y) = (min_y.floor() as isize, (min_y + 1.0).ceil() as isize - 1);
+            for x in first_x..=last_x {
+                for y in first_y..=last_y {
+                    if !cells.contains(&(x, y)) {
+                        cells.push((x, y));
+                    }
+                }
+            }
+        }
+        cells
+    }
+
+    // Whether the organism still has energy and is younger than its max_age,
+    // the same checks next_frame uses to decide that it dies.
+    pub fn is_alive(&self) -> bool {
+        self.energy > 0 && self.age < self.attributes.max_age
+    }
+
+    // Age times energy: organisms that both last and stay well fed score
+    // highest.
+    pub fn fitness(&self) -> f64 {
+        self.age as f64 * self.energy as f64
+    }
+
+    pub fn gain_energy(&mut self, amount: isize) {
+        self.energy = self
+            .energy
+            .saturating_add(amount)
+            .clamp(0, self.attributes.max_energy.max(0));
+    }
+
+    fn offspring_location(&self, rng: &mut impl Rng) -> Vector2<isize> {
+        let location_offset_x = rng.gen_range(-1..=1);
+        let location_offset_y = rng.gen_range(-1..=1);
+        self.location + Vector2::new(location_offset_x, location_offset_y)
+    }
+
+    // An exact copy under a new id, recorded as a child of this organism. Unlike
+    // reproduce, nothing is mutated and no energy changes hands. The copy gets
+    // a stream of its own, drawn from the source's, so the two do not go on to
+    // make the same rolls.
+    pub fn clone_as(&self, new_id: isize) -> Organism {
+        let mut source = self.rng.clone();
+        Organism {
+            id: new_id,
+            parent_id: Some(self.id),
+            rng: seeded_rng(source.gen(), new_id),
+            ..self.clone()
+        }
+    }
+
+    pub fn reproduce(
+        &self,
+        new_id: isize,
+        rng: &mut impl Rng,
+    ) -> Result<Organism, ReproductionError> {
+        self.reproduce_using(new_id, rng, &DefaultMutator)
+    }
+
+    // Like reproduce, with `mutator` in place of the built-in mutation.
+    pub fn reproduce_using(
+        &self,
+        new_id: isize,
+        rng: &mut impl Rng,
+        mutator: &dyn Mutator,
+    ) -> Result<Organism, ReproductionError> {
+        if self.energy <= self.attributes.min_reproduction_energy || self.offspring_energy() <= 0 {
+            return Err(ReproductionError::InsufficientEnergy {
+                energy: self.energy,
+            });
+        }
+        let mut offspring = Self {
+            id: new_id,
+            genome: self.genome.clone(),
+            energy: 0,
+            age: 0,
+            location: self.offspring_location(rng),
+            body_squares: self.body_squares.clone(),
+            current_body_state: 0,
+            body_state_reversed: false,
+            attributes: self.attributes.clone(),
+            base_attributes: self.base_attributes.clone(),
+            target_body: None,
+            parent_id: Some(self.id),
+            rng: Pcg64::seed_from_u64(rng.gen()),
+            behavior_state: BehaviorState::default(),
+        };
+
+        offspring.gain_energy(self.offspring_energy());
+        mutator.mutate(&mut offspring, rng);
+        self.check_offspring(&offspring)?;
+        Ok(offspring)
+    }
+
+    pub fn reproduce_with(
+        &mut self,
+        partner: &Organism,
+        new_id: isize,
+        rng: &mut impl Rng,
+    ) -> Result<Organism, ReproductionError> {
+        self.reproduce_with_using(partner, new_id, rng, &DefaultMutator)
+    }
+
+    // Sexual reproduction: the child's genome is a crossover of both parents'.
+    // No frame charges for it, so unlike reproduce this organism pays for the
+    // child itself, and only when one is produced.
+    pub fn reproduce_with_using(
+        &mut self,
+        partner: &Organism,
+        new_id: isize,
+        rng: &mut impl Rng,
+        mutator: &dyn Mutator,
+    ) -> Result<Organism, ReproductionError> {
+        if self.energy <= self.attributes.min_reproduction_energy || self.offspring_energy() <= 0 {
+            return Err(ReproductionError::InsufficientEnergy {
+                energy: self.energy,
+            });
+        }
+        let genome = self.genome.crossover(&partner.genome, rng);
+        let mut offspring = Organism::from_template(new_id, genome, &self.base_attributes)
+            .map_err(|_| ReproductionError::InvalidBlueprint)?;
+        offspring.energy = 0;
+        offspring.location = self.offspring_location(rng);
+        offspring.parent_id = Some(self.id);
+        offspring.rng = Pcg64::seed_from_u64(rng.gen());
+        offspring.gain_energy(self.offspring_energy());
+        mutator.mutate(&mut offspring, rng);
+        self.check_offspring(&offspring)?;
+        self.energy -= offspring.energy;
+        Ok(offspring)
+    }
+
+    // A child may only have body states within max_size that this organism's
+    // body could produce.
+    fn check_offspring(&self, offspring: &Organism) -> Result<(), ReproductionError> {
+        let invalid = offspring.attributes.body_states.iter().any(|body| {
+            body.size() as isize > self.attributes.max_size
+                || !self.body_squares.check_blueprint_validity(&body.squares)
+        });
+        if invalid {
+            return Err(ReproductionError::InvalidBlueprint);
+        }
+        Ok(())
+    }
+
+    // The share of the parent's energy handed to a child. The child may keep
+    // less if it is over its own max_energy, and the parent is charged only
+    // for what the child actually holds, so no energy is created.
+    fn offspring_energy(&self) -> isize {
+        (self.energy.max(0) as f64 * self.attributes.split_fraction as f64) as isize
+    }
+
+    // Upkeep grows with body size as count^metabolism_exponent, movement is
+    // charged per cell of displacement, and changing shape per square that
+    // differs from the previous body state. Costs are summed as floats, and the
+    // final cast saturates, so large bodies cannot overflow.
+    fn frame_cost(&self, ds: Vector2<isize>, changed_squares: usize) -> isize {
+        let squares = self.body_squares.size() as f64;
+        let upkeep = self.attributes.metabolism as f64
+            * squares.powf(self.attributes.metabolism_exponent as f64)
+            * self.senescence()
+            * self.compactness();
+        let movement_cost =
+            self.attributes.movement_cost_factor as f64 * (ds.x as f64).hypot(ds.y as f64);
+        let transition_cost =
+            self.attributes.transition_cost_factor as f64 * changed_squares as f64;
+        (upkeep + movement_cost + transition_cost) as isize
+    }
+
+    // Upkeep multiplier of 1 + senescence_factor * age / max_age, so older
+    // organisms burn more energy without their stored metabolism changing.
+    fn senescence(&self) -> f64 {
+        if self.attributes.max_age <= 0 {
+            return 1.0;
+        }
+        let life_fraction = self.age as f64 / self.attributes.max_age as f64;
+        1.0 + self.attributes.senescence_factor as f64 * life_fraction
+    }
+
+    // Upkeep multiplier of 1 + compactness_factor * (sprawl - 1), so bodies
+    // that fill their bounding box pay the plain rate.
+    fn compactness(&self) -> f64 {
+        match self.body_squares.sprawl() {
+            Some(sprawl) => {
+                1.0 + self.attributes.compactness_factor as f64 * (sprawl - 1.0).max(0.0)
+            }
+            None => 1.0,
+        }
+    }
+
+    // The body for this frame while a target body is set: one square closer
+    // to the target, or unchanged while the organism is at max_size or cannot
+    // pay for the new square. The target is dropped once it is reached or no
+    // square of it can be attached, and body states take over again. A body
+    // that reached its target replaces the upcoming body state, so the grown
+    // shape is kept.
+    fn grow_toward_target(&mut self) -> Option<Body> {
+        let target = self.target_body.as_ref()?;
+        let mut body = self.body_squares.clone();
+        if body.covers(target) {
+            self.target_body = None;
+            return None;
+        }
+        let at_max_size = body.size() as isize >= self.attributes.max_size;
+        let can_pay = self.energy as f64 > self.attributes.transition_cost_factor as f64;
+        if at_max_size || !can_pay {
+            return Some(body);
+        }
+        if !body.grow_toward(target) {
+            self.target_body = None;
+            return None;
+        }
+        if body.covers(target) {
+            self.target_body = None;
+            let upcoming = usize::try_from(self.current_body_state).ok();
+            if let Some(state) =
+                upcoming.and_then(|index| self.attributes.body_states.get_mut(index))
+            {
+                *state = body.clone();
+            }
+        }
+        Some(body)
+    }
+
+    // Picks the body state adopted on the next frame.
+    pub fn set_body_state(&mut self, index: usize) -> Result<(), OrganismError> {
+        let len = self.attributes.body_states.len();
+        if index >= len {
+            return Err(OrganismError::BodyStateOutOfRange {
+                index: index as isize,
+                len,
+            });
+        }
+        self.current_body_state = index as isize;
+        Ok(())
+    }
+
+    fn advance_body_state(&mut self) {
+        let last = self.attributes.body_states.len() as isize - 1;
+        match self.attributes.cycle_mode {
+            CycleMode::Forward => {
+                if self.current_body_state < last {
+                    self.current_body_state += 1;
+                } else {
+                    self.current_body_state = 0;
+                }
+            }
+            CycleMode::PingPong => {
+                if last <= 0 {
+                    self.current_body_state = 0;
+                    return;
+                }
+                if self.current_body_state >= last {
+                    self.body_state_reversed = true;
+                } else if self.current_body_state <= 0 {
+                    self.body_state_reversed = false;
+                }
+                if self.body_state_reversed {
+                    self.current_body_state -= 1;
+                } else {
+                    self.current_body_state += 1;
+                }
+            }
+        }
+    }
+
+    // Index of the body state to take on next, as decided by the behavior mode
+    // from the behavior state. Without a behavioral override this is the
+    // current body state.
+    pub fn choose_body_state(&self) -> usize {
+        match self.attributes.behavior_mode {
+            BehaviorMode::FeedWhenHungry { body_state, hunger }
+                if self.behavior_state.hunger >= hunger
+                    && body_state < self.attributes.body_states.len() =>
+            {
+                body_state
+            }
+            _ => self.current_body_state.max(0) as usize,
+        }
+    }
+
+    fn update_behavior_state(&mut self) {
+        let max_energy = self.attributes.max_energy.max(1) as f32;
+        self.behavior_state.hunger = (1.0 - self.energy as f32 / max_energy).clamp(0.0, 1.0);
+    }
+
+    // The body state the organism moves into on its next frame.
+    pub fn current_body_blueprint(&self) -> Result<&Body, OrganismError> {
+        if self.attributes.body_states.is_empty() {
+            return Err(OrganismError::EmptyBodyStates);
+        }
+        usize::try_from(self.current_body_state)
+            .ok()
+            .and_then(|index| self.attributes.body_states.get(index))
+            .ok_or(OrganismError::BodyStateOutOfRange {
+                index: self.current_body_state,
+                len: self.attributes.body_states.len(),
+            })
+    }
+
+    // Like next_frame, but drawing from the organism's own stream. Offspring
+    // get streams seeded from it.
+    pub fn step(
+        &mut self,
+        offspring_id: isize,
+    ) -> Result<(OrganismState, Option<Organism>), OrganismError> {
+        self.step_using(offspring_id, &DefaultMutator)
+    }
+
+    pub fn step_using(
+        &mut self,
+        offspring_id: isize,
+        mutator: &dyn Mutator,
+    ) -> Result<(OrganismState, Option<Organism>), OrganismError> {
+        let mut rng = self.rng.clone();
+        let frame = self.next_frame_using(offspring_id, &mut rng, mutator);
+        self.rng = rng;
+        frame
+    }
+
+    pub fn next_frame(
+        &mut self,
+        offspring_id: isize,
+        rng: &mut impl Rng,
+    ) -> Result<(OrganismState, Option<Organism>), OrganismError> {
+        self.next_frame_using(offspring_id, rng, &DefaultMutator)
+    }
+
+    // Like next_frame, with `mutator` in place of the built-in mutation, both
+    // for this organism and for its offspring.
+    pub fn next_frame_using(
+        &mut self,
+        offspring_id: isize,
+        rng: &mut impl Rng,
+        mutator: &dyn Mutator,
+    ) -> Result<(OrganismState, Option<Organism>), OrganismError> {
+        // Conditional genes may switch on or off as energy changes.
+        if self
+            .genome
+            .genes
+            .iter()
+            .any(|gene| gene.expression_condition != ExpressionCondition::Always)
+        {
+            self.rederive_attributes();
+        }
+        self.update_behavior_state();
+        let next_body = match self.grow_toward_target() {
+            Some(body) => body,
+            None => {
+                let blueprint = self.current_body_blueprint()?.clone();
+                let chosen = self.choose_body_state();
+                if chosen as isize == self.current_body_state {
+                    self.advance_body_state();
+                    blueprint
+                } else {
+                    // Behavioral overrides pause the cycle instead of moving it on.
+                    self.attributes.body_states[chosen].clone()
+                }
+            }
+        };
+        let prev_body = &self.body_squares;
+        let ds = Self::calculate_movement(prev_body, &next_body, self.attributes.movement_scale);
+        let ds = self.attributes.movement_mode.quantize(ds);
+        let changed_squares = prev_body.changed_squares(&next_body);
+        self.location += ds;
+        if ds != Vector2::zeros() {
+            self.behavior_state.last_moved = self.age;
+        }
+        self.body_squares = next_body;
+        self.energy = self
+            .energy
+            .saturating_sub(self.frame_cost(ds, changed_squares));
+
+        if self.energy <= 0 {
+            return Ok((OrganismState::Dead(DeathCause::Starvation), None));
+        }
+
+        self.age += 1;
+        if self
+            .attributes
+            .mortality_model
+            .dies(self.age, self.attributes.max_age, rng)
+        {
+            return Ok((OrganismState::Dead(DeathCause::OldAge), None));
+        }
+        let will_mutate = rng.gen_range(0.0..1.0) < self.attributes.mutation_rate;
+        if will_mutate {
+            log::trace!("organism {} mutated", self.id);
+            let cost = mutator.cost(self);
+            self.energy = self.energy.saturating_sub(cost as isize);
+            if self.energy <= 0 {
+                return Ok((OrganismState::Dead(DeathCause::Starvation), None));
+            }
+            mutator.mutate(self, rng);
+        }
+        if self.age < self.attributes.puberty_age
+            || self.energy <= self.attributes.min_reproduction_energy
+        {
+            return Ok((OrganismState::Alive, None));
+        }
+        let will_reproduce = rng.gen_range(0.0..1.0) < self.attributes.reproduction_rate;
+        if !will_reproduce {
+            return Ok((OrganismState::Alive, None));
+        }
+
+        match self.reproduce_using(offspring_id, rng, mutator) {
+            Ok(offspring) => {
+                // The parent only pays for offspring that are actually born.
+                self.energy -= offspring.energy;
+                Ok((OrganismState::Alive, Some(offspring)))
+            }
+            Err(error) => {
+                log::debug!("organism {} could not reproduce: {}", self.id, error);
+                Ok((OrganismState::Alive, None))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square() -> Body {
+        let mut body = Body::new();
+        body.add_square(BodySquare {
+            position: Vector2::new(0.0, 0.0),
+        });
+        body
+    }
+
+    #[test]
+    fn rederiving_clamps_energy_to_the_new_cap() {
+        let genome = Genome::builder()
+            .max_energy(0)
+            .body_state(square())
+            .build()
+            .unwrap();
+        let mut organism = Organism::new(0, genome).unwrap();
+        organism.energy = organism.attributes.max_energy;
+        organism.genome.genes[0].attribute_type = AttributeType::MaxEnergy(-500);
+        organism.rederive_attributes();
+        assert!(organism.attributes.max_energy < organism.base_attributes.max_energy);
+        assert_eq!(organism.energy, organism.attributes.max_energy);
+    }
+
+    #[test]
+    fn clones_roll_differently_from_their_source() {
+        let genome = Genome::builder().body_state(square()).build().unwrap();
+        let mut source = Organism::new(0, genome).unwrap();
+        let mut copy = source.clone_as(1);
+        let rolls =
+            |organism: &mut Organism| -> Vec<u64> { (0..4).map(|_| organism.rng.gen()).collect() };
+        assert_ne!(rolls(&mut source), rolls(&mut copy));
+    }
+
+    fn parent() -> Organism {
+        let genome = Genome::builder().body_state(square()).build().unwrap();
+        let mut organism = Organism::new(0, genome).unwrap();
+        organism.attributes.mutation_rate = 0.0;
+        organism.energy = organism.attributes.max_energy;
+        organism
+    }
+
+    #[test]
+    fn reproducing_without_energy_fails() {
+        let mut rng = Pcg64::seed_from_u64(2);
+        let mut organism = parent();
+        organism.energy = organism.attributes.min_reproduction_energy;
+        let partner = parent();
+        let expected = ReproductionError::InsufficientEnergy {
+            energy: organism.energy,
+        };
+        assert_eq!(organism.reproduce(1, &mut rng).unwrap_err(), expected);
+        assert_eq!(
+            organism.reproduce_with(&partner, 1, &mut rng).unwrap_err(),
+            expected
+        );
+    }
+
+    #[test]
+    fn oversized_offspring_are_invalid() {
+        let mut rng = Pcg64::seed_from_u64(3);
+        let mut organism = parent();
+        organism.attributes.max_size = 0;
+        let partner = parent();
+        let energy = organism.energy;
+        assert_eq!(
+            organism.reproduce(1, &mut rng).unwrap_err(),
+            ReproductionError::InvalidBlueprint
+        );
+        assert_eq!(
+            organism.reproduce_with(&partner, 1, &mut rng).unwrap_err(),
+            ReproductionError::InvalidBlueprint
+        );
+        assert_eq!(organism.energy, energy);
+    }
+
+    #[test]
+    fn sexual_reproduction_charges_the_parent() {
+        let mut rng = Pcg64::seed_from_u64(4);
+        let mut organism = parent();
+        let partner = parent();
+        let before = organism.energy;
+        let child = organism.reproduce_with(&partner, 1, &mut rng).unwrap();
+        assert!(child.energy > 0);
+        assert_eq!(organism.energy + child.energy, before);
+    }
+
+    #[test]
+    fn conditional_genes_see_the_starting_energy() {
+        let genome = Genome::builder()
+            .max_energy(1000)
+            .expressed_when(ExpressionCondition::EnergyBelow(10))
+            .body_state(square())
+            .build()
+            .unwrap();
+        let organism = Organism::new(0, genome).unwrap();
+        let defaults = Attribute::default_attributes();
+        assert_eq!(organism.attributes.max_energy, defaults.max_energy);
+        let starting_energy = defaults.max_energy as f64 * defaults.starting_energy_fraction as f64;
+        assert_eq!(organism.energy, starting_energy as isize);
+    }
+
+    #[test]
+    fn jitter_grows_with_the_mutation_rate() {
+        let mut rng = Pcg64::seed_from_u64(34);
+        let body = square();
+        let mut spread = |rate: f32| {
+            let mut organism = parent();
+            organism.attributes.grid_aligned = false;
+            organism.attributes.mutation_rate = rate;
+            (0..500)
+                .map(|_| {
+                    organism.jitter_body(&body, &mut rng).squares[0]
+                        .position
+                        .norm_squared()
+                })
+                .sum::<f64>()
+                / 500.0
+        };
+        assert_eq!(spread(0.0), 0.0);
+        assert!(spread(0.5) > 100.0 * spread(0.01));
+    }
+
+    #[test]
+    fn rate_drift_scales_with_the_mutation_rate() {
+        let mut rng = Pcg64::seed_from_u64(1);
+        let mut gene = Gene {
+            id: 0,
+            name: "rate".into(),
+            value: 0,
+            attribute_type: AttributeType::ReproductionRate(0.5),
+            weight: 1.0,
+            expression_condition: ExpressionCondition::Always,
+        };
+        let rate = |gene: &Gene| match gene.attribute_type {
+            AttributeType::ReproductionRate(rate) => rate,
+            _ => unreachable!(),
+        };
+        gene.perturb(0.0, &mut rng);
+        assert_eq!(rate(&gene), 0.5);
+        for _ in 0..100 {
+            let before = rate(&gene);
+            gene.perturb(0.2, &mut rng);
+            assert!((rate(&gene) - before).abs() <= 0.2 * FLOAT_GENE_DRIFT_SCALE);
         }
     }
 }
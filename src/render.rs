@@ -0,0 +1,86 @@
+use crate::world::World;
+use std::fmt::Write;
+
+// Hue derived from a fixed integer mix of the id, so an organism keeps its
+// colour between frames and between runs.
+pub fn organism_hue(id: isize) -> u32 {
+    let mut hash = id as u64;
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^= hash >> 33;
+    (hash % 360) as u32
+}
+
+pub fn to_svg(world: &World) -> String {
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {} {}">"#,
+        world.size.x, world.size.y
+    );
+    for organism in &world.organisms {
+        let hue = organism_hue(organism.id);
+        for square in organism.body_squares.squares() {
+            let x = organism.location.x as f64 + square.position.x;
+            let y = organism.location.y as f64 + square.position.y;
+            let _ = writeln!(
+                svg,
+                r#"  <rect x="{}" y="{}" width="1" height="1" fill="hsl({}, 70%, 50%)"/>"#,
+                x, y, hue
+            );
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// Same colour as the SVG fill, hsl(hue, 70%, 50%), as RGB bytes.
+fn hue_to_rgb(hue: u32) -> [u8; 3] {
+    let (saturation, lightness) = (0.7, 0.5);
+    let chroma = (1.0 - (2.0 * lightness - 1.0_f64).abs()) * saturation;
+    let sector = hue as f64 / 60.0;
+    let secondary = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, secondary, 0.0),
+        1 => (secondary, chroma, 0.0),
+        2 => (0.0, chroma, secondary),
+        3 => (0.0, secondary, chroma),
+        4 => (secondary, 0.0, chroma),
+        _ => (chroma, 0.0, secondary),
+    };
+    let offset = lightness - chroma / 2.0;
+    let channel = |value: f64| ((value + offset) * 255.0).round() as u8;
+    [channel(r), channel(g), channel(b)]
+}
+
+// Paints every occupied world cell into an RGBA buffer of `width * height`
+// pixels. Each cell covers at least one pixel, and anything outside the world
+// is clipped.
+pub fn rasterize(world: &World, width: usize, height: usize) -> Vec<u8> {
+    let mut buffer = vec![0; width * height * 4];
+    if world.size.x == 0 || world.size.y == 0 {
+        return buffer;
+    }
+    let pixel_span = |cell: isize, pixels: usize, cells: usize| {
+        let start = cell as usize * pixels / cells;
+        let end = ((cell as usize + 1) * pixels / cells).max(start + 1);
+        start..end.min(pixels)
+    };
+    for organism in &world.organisms {
+        let [r, g, b] = hue_to_rgb(organism_hue(organism.id));
+        for (x, y) in organism.occupied_cells() {
+            if x < 0 || y < 0 || x as usize >= world.size.x || y as usize >= world.size.y {
+                continue;
+            }
+            for py in pixel_span(y, height, world.size.y) {
+                for px in pixel_span(x, width, world.size.x) {
+                    let offset = (py * width + px) * 4;
+                    buffer[offset..offset + 4].copy_from_slice(&[r, g, b, 255]);
+                }
+            }
+        }
+    }
+    buffer
+}
@@ -0,0 +1,42 @@
+use crate::stats::PopulationStats;
+use crate::world::{StepSummary, World, WorldError};
+use std::sync::{Arc, PoisonError, RwLock};
+
+// A world that can be stepped on one thread while others read it. Clones share
+// the same world.
+#[derive(Clone)]
+pub struct SharedWorld {
+    world: Arc<RwLock<World>>,
+}
+
+// Fails to compile if World ever stops being shareable across threads.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<World>();
+};
+
+impl SharedWorld {
+    pub fn new(world: World) -> Self {
+        Self {
+            world: Arc::new(RwLock::new(world)),
+        }
+    }
+
+    // A panic while the lock was held leaves the world as it was at that
+    // point, which is still safe to read, so poisoning is ignored.
+    pub fn read<R>(&self, f: impl FnOnce(&World) -> R) -> R {
+        f(&self.world.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    pub fn write<R>(&self, f: impl FnOnce(&mut World) -> R) -> R {
+        f(&mut self.world.write().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    pub fn read_stats(&self) -> PopulationStats {
+        self.read(World::stats)
+    }
+
+    pub fn step(&self) -> Result<StepSummary, WorldError> {
+        self.write(World::step)
+    }
+}
@@ -0,0 +1,64 @@
+use crate::stats::PopulationStats;
+use crate::world::{World, WorldError};
+
+pub const DEFAULT_MAX_TICKS: usize = 100_000;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StopReason {
+    Condition,
+    Extinct,
+    MaxTicks,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RunReport {
+    pub ticks: usize,
+    pub reason: StopReason,
+    pub stats: PopulationStats,
+}
+
+pub struct Simulation {
+    pub world: World,
+    pub max_ticks: usize,
+}
+
+impl Simulation {
+    pub fn new(world: World) -> Self {
+        Self {
+            world,
+            max_ticks: DEFAULT_MAX_TICKS,
+        }
+    }
+
+    pub fn with_max_ticks(mut self, max_ticks: usize) -> Self {
+        self.max_ticks = max_ticks;
+        self
+    }
+
+    // Steps the world until `stop` returns true for the world and the number
+    // of ticks run so far, the population dies out, or `max_ticks` is reached.
+    pub fn run_until(
+        &mut self,
+        stop: impl Fn(&World, usize) -> bool,
+    ) -> Result<RunReport, WorldError> {
+        let mut ticks = 0;
+        let reason = loop {
+            if stop(&self.world, ticks) {
+                break StopReason::Condition;
+            }
+            if self.world.organisms.is_empty() {
+                break StopReason::Extinct;
+            }
+            if ticks >= self.max_ticks {
+                break StopReason::MaxTicks;
+            }
+            self.world.step()?;
+            ticks += 1;
+        };
+        Ok(RunReport {
+            ticks,
+            reason,
+            stats: self.world.stats(),
+        })
+    }
+}
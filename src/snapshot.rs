@@ -0,0 +1,55 @@
+use crate::world::World;
+use nalgebra::Vector2;
+use std::collections::BTreeMap;
+
+// Where every organism was at one tick, keyed by id.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct WorldSnapshot {
+    pub tick: u64,
+    pub locations: BTreeMap<isize, Vector2<isize>>,
+}
+
+// Ids that changed between two snapshots, each list in ascending order.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct WorldDiff {
+    pub moved: Vec<isize>,
+    pub born: Vec<isize>,
+    pub died: Vec<isize>,
+}
+
+impl WorldDiff {
+    pub fn is_empty(&self) -> bool {
+        self.moved.is_empty() && self.born.is_empty() && self.died.is_empty()
+    }
+}
+
+impl WorldSnapshot {
+    pub fn of(world: &World) -> Self {
+        Self {
+            tick: world.tick(),
+            locations: world
+                .organisms
+                .iter()
+                .map(|organism| (organism.id, organism.location))
+                .collect(),
+        }
+    }
+
+    pub fn diff(&self, prev: &WorldSnapshot) -> WorldDiff {
+        let mut diff = WorldDiff::default();
+        for (id, location) in &self.locations {
+            match prev.locations.get(id) {
+                Some(previous) if previous != location => diff.moved.push(*id),
+                Some(_) => {}
+                None => diff.born.push(*id),
+            }
+        }
+        diff.died = prev
+            .locations
+            .keys()
+            .filter(|id| !self.locations.contains_key(id))
+            .copied()
+            .collect();
+        diff
+    }
+}
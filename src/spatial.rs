@@ -0,0 +1,144 @@
+use crate::organism::Organism;
+use nalgebra::Vector2;
+use std::collections::HashMap;
+
+pub const DEFAULT_CELL_SIZE: isize = 8;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SpatialGrid {
+    cell_size: isize,
+    cells: HashMap<(isize, isize), Vec<usize>>,
+    locations: Vec<Vector2<isize>>,
+    // Smallest and largest occupied cell on each axis, if any.
+    bounds: Option<((isize, isize), (isize, isize))>,
+}
+
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        Self::new(DEFAULT_CELL_SIZE)
+    }
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: isize) -> Self {
+        Self {
+            cell_size: cell_size.max(1),
+            cells: HashMap::new(),
+            locations: Vec::new(),
+            bounds: None,
+        }
+    }
+
+    pub fn build(organisms: &[Organism], cell_size: isize) -> Self {
+        let mut grid = Self::new(cell_size);
+        grid.rebuild(organisms);
+        grid
+    }
+
+    pub fn rebuild(&mut self, organisms: &[Organism]) {
+        self.cells.clear();
+        self.locations.clear();
+        self.bounds = None;
+        for (index, organism) in organisms.iter().enumerate() {
+            let cell = self.cell_of(organism.location);
+            self.cells.entry(cell).or_default().push(index);
+            self.locations.push(organism.location);
+            let ((min_x, min_y), (max_x, max_y)) = self.bounds.unwrap_or((cell, cell));
+            self.bounds = Some((
+                (min_x.min(cell.0), min_y.min(cell.1)),
+                (max_x.max(cell.0), max_y.max(cell.1)),
+            ));
+        }
+    }
+
+    fn cell_of(&self, location: Vector2<isize>) -> (isize, isize) {
+        (
+            location.x.div_euclid(self.cell_size),
+            location.y.div_euclid(self.cell_size),
+        )
+    }
+
+    // Indices of organisms whose location lies within `radius` (inclusive,
+    // euclidean) of `pos`, in ascending order.
+    pub fn neighbors_within(&self, pos: Vector2<isize>, radius: isize) -> Vec<usize> {
+        if radius < 0 {
+            return Vec::new();
+        }
+        let (min_x, min_y) = self.cell_of(pos - Vector2::new(radius, radius));
+        let (max_x, max_y) = self.cell_of(pos + Vector2::new(radius, radius));
+        let mut neighbors = Vec::new();
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                let Some(indices) = self.cells.get(&(x, y)) else {
+                    continue;
+                };
+                for &index in indices {
+                    let offset = self.locations[index] - pos;
+                    if offset.x * offset.x + offset.y * offset.y <= radius * radius {
+                        neighbors.push(index);
+                    }
+                }
+            }
+        }
+        neighbors.sort_unstable();
+        neighbors
+    }
+
+    // Index of the organism closest to `pos` by euclidean distance, along with
+    // its squared distance, skipping any index `skip` accepts. Cells are
+    // searched in growing rings around `pos`, so only nearby cells are visited
+    // once something close has been found. Ties go to the lowest index.
+    pub fn nearest(
+        &self,
+        pos: Vector2<isize>,
+        skip: impl Fn(usize) -> bool,
+    ) -> Option<(usize, isize)> {
+        let (center_x, center_y) = self.cell_of(pos);
+        // No occupied cell lies further out than the farthest bound.
+        let ((min_x, min_y), (max_x, max_y)) = self.bounds?;
+        let max_ring = [
+            min_x - center_x,
+            max_x - center_x,
+            min_y - center_y,
+            max_y - center_y,
+        ]
+        .into_iter()
+        .map(isize::abs)
+        .max()?;
+        let mut best: Option<(usize, isize)> = None;
+        for ring in 0..=max_ring {
+            // Anything in this ring or beyond is at least this far away.
+            let reach = (ring - 1).max(0) * self.cell_size;
+            if best.is_some_and(|(_, distance)| reach * reach > distance) {
+                break;
+            }
+            for x in center_x - ring..=center_x + ring {
+                for y in center_y - ring..=center_y + ring {
+                    if (x - center_x).abs().max((y - center_y).abs()) != ring {
+                        continue;
+                    }
+                    let Some(indices) = self.cells.get(&(x, y)) else {
+                        continue;
+                    };
+                    for &index in indices {
+                        if skip(index) {
+                            continue;
+                        }
+                        let offset = self.locations[index] - pos;
+                        let distance = offset.x * offset.x + offset.y * offset.y;
+                        let closer = match best {
+                            Some((best_index, best_distance)) => {
+                                (distance, index) < (best_distance, best_index)
+                            }
+                            None => true,
+                        };
+                        if closer {
+                            best = Some((index, distance));
+                        }
+                    }
+                }
+            }
+        }
+        best
+    }
+}
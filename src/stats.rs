@@ -0,0 +1,214 @@
+use crate::organism::Organism;
+use nalgebra::Vector2;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Summary {
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct PopulationStats {
+    pub population: usize,
+    pub energy: Option<Summary>,
+    pub age: Option<Summary>,
+    pub body_size: Option<Summary>,
+    pub metabolism: Option<Summary>,
+    pub reproduction_rate: Option<Summary>,
+    pub mutation_rate: Option<Summary>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AttrSelector {
+    Energy,
+    Age,
+    Size,
+    Metabolism,
+}
+
+impl AttrSelector {
+    pub fn value(&self, organism: &Organism) -> f64 {
+        match self {
+            AttrSelector::Energy => organism.energy as f64,
+            AttrSelector::Age => organism.age as f64,
+            AttrSelector::Size => organism.body_squares.size() as f64,
+            AttrSelector::Metabolism => organism.attributes.metabolism as f64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Accumulator {
+    count: usize,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for Accumulator {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl Accumulator {
+    fn add(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    fn merge(&mut self, other: &Accumulator) {
+        self.count += other.count;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    fn summary(&self) -> Option<Summary> {
+        if self.count == 0 {
+            return None;
+        }
+        Some(Summary {
+            mean: self.sum / self.count as f64,
+            min: self.min,
+            max: self.max,
+        })
+    }
+}
+
+// Organisms are summed in chunks of this size, and the chunk totals are then
+// combined in order. Serial and parallel stats add floats in exactly the same
+// order that way, so they agree bit for bit.
+const CHUNK_SIZE: usize = 4096;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Totals {
+    energy: Accumulator,
+    age: Accumulator,
+    body_size: Accumulator,
+    metabolism: Accumulator,
+    reproduction_rate: Accumulator,
+    mutation_rate: Accumulator,
+}
+
+impl Totals {
+    fn of(organisms: &[Organism]) -> Self {
+        let mut totals = Totals::default();
+        for organism in organisms {
+            totals.energy.add(organism.energy as f64);
+            totals.age.add(organism.age as f64);
+            totals.body_size.add(organism.body_squares.size() as f64);
+            totals.metabolism.add(organism.attributes.metabolism as f64);
+            totals
+                .reproduction_rate
+                .add(organism.attributes.reproduction_rate as f64);
+            totals
+                .mutation_rate
+                .add(organism.attributes.mutation_rate as f64);
+        }
+        totals
+    }
+
+    fn merge(mut self, other: Totals) -> Self {
+        self.energy.merge(&other.energy);
+        self.age.merge(&other.age);
+        self.body_size.merge(&other.body_size);
+        self.metabolism.merge(&other.metabolism);
+        self.reproduction_rate.merge(&other.reproduction_rate);
+        self.mutation_rate.merge(&other.mutation_rate);
+        self
+    }
+
+    fn stats(&self, population: usize) -> PopulationStats {
+        PopulationStats {
+            population,
+            energy: self.energy.summary(),
+            age: self.age.summary(),
+            body_size: self.body_size.summary(),
+            metabolism: self.metabolism.summary(),
+            reproduction_rate: self.reproduction_rate.summary(),
+            mutation_rate: self.mutation_rate.summary(),
+        }
+    }
+}
+
+impl PopulationStats {
+    pub fn from_organisms(organisms: &[Organism]) -> Self {
+        organisms
+            .chunks(CHUNK_SIZE)
+            .map(Totals::of)
+            .fold(Totals::default(), Totals::merge)
+            .stats(organisms.len())
+    }
+
+    // Same result as from_organisms, with chunks summed across threads.
+    #[cfg(feature = "rayon")]
+    pub fn from_organisms_parallel(organisms: &[Organism]) -> Self {
+        let chunks: Vec<Totals> = organisms.par_chunks(CHUNK_SIZE).map(Totals::of).collect();
+        chunks
+            .into_iter()
+            .fold(Totals::default(), Totals::merge)
+            .stats(organisms.len())
+    }
+}
+
+// Counts organisms in `bins` equal-width bins between the smallest and largest
+// value of the attribute. The largest value falls in the last bin. When every
+// value is the same they all land in the first bin.
+pub fn histogram(organisms: &[Organism], attr: AttrSelector, bins: usize) -> Vec<usize> {
+    let mut counts = vec![0; bins];
+    if bins == 0 {
+        return counts;
+    }
+    let values: Vec<f64> = organisms
+        .iter()
+        .map(|organism| attr.value(organism))
+        .collect();
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let width = (max - min) / bins as f64;
+    for value in values {
+        let bin = if width > 0.0 {
+            (((value - min) / width) as usize).min(bins - 1)
+        } else {
+            0
+        };
+        counts[bin] += 1;
+    }
+    counts
+}
+
+// Organism counts per `cell` x `cell` block of a grid of the given size, as
+// rows of blocks from the top. The last block on each axis is smaller when the
+// size is not a multiple of `cell`. Organisms are counted at their location,
+// and ones off the grid are left out.
+pub fn density_grid(organisms: &[Organism], size: Vector2<usize>, cell: usize) -> Vec<Vec<usize>> {
+    if cell == 0 {
+        return Vec::new();
+    }
+    let columns = size.x.div_ceil(cell);
+    let rows = size.y.div_ceil(cell);
+    let mut grid = vec![vec![0; columns]; rows];
+    for organism in organisms {
+        let (Ok(x), Ok(y)) = (
+            usize::try_from(organism.location.x),
+            usize::try_from(organism.location.y),
+        ) else {
+            continue;
+        };
+        if x < size.x && y < size.y {
+            grid[y / cell][x / cell] += 1;
+        }
+    }
+    grid
+}
@@ -1,7 +1,1116 @@
+use crate::config::{ConfigError, ScenarioConfig, WorldConfig};
+use crate::field;
+use crate::mutation::{DefaultMutator, Mutator};
+use crate::observer::{NoopObserver, SimObserver};
 use crate::organism;
+use crate::organism::{DeathCause, OrganismError, OrganismState, ReproductionError};
+use crate::render;
+use crate::snapshot::WorldSnapshot;
+use crate::spatial::SpatialGrid;
+use crate::stats::{self, AttrSelector, PopulationStats};
 use nalgebra::Vector2;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io;
+#[cfg(feature = "serde")]
+use std::{fs::File, io::BufWriter, io::Write, path::Path};
 
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IdAllocator {
+    next: isize,
+}
+
+impl IdAllocator {
+    pub fn new(start: isize) -> Self {
+        Self { next: start }
+    }
+
+    pub fn peek(&self) -> isize {
+        self.next
+    }
+
+    pub fn allocate(&mut self) -> isize {
+        let id = self.next;
+        self.next += 1;
+        id
+    }
+
+    // Makes sure ids handed out from now on cannot collide with `id`.
+    pub fn reserve_past(&mut self, id: isize) {
+        self.next = self.next.max(id + 1);
+    }
+}
+
+pub const FOOD_ENERGY: isize = 200;
+// Written into every saved world. Bump it whenever the saved layout changes.
+pub const CURRENT_FORMAT_VERSION: u32 = 1;
+// Share of a prey's energy that reaches the predator.
+pub const PREDATION_EFFICIENCY: f64 = 0.5;
+// The cells around a parent where a newborn may be placed, clockwise from the
+// top left.
+const PLACEMENT_RING: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+];
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BoundaryPolicy {
+    #[default]
+    Clamp,
+    Wrap,
+    Kill,
+}
+
+// Which organisms go first when the population is over max_population.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CullPolicy {
+    #[default]
+    LowestEnergy,
+    Oldest,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Food {
+    pub position: Vector2<isize>,
+    pub energy: isize,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct World {
+    // First, so binary loaders can read it before the rest. Worlds saved
+    // before versioning have none and read as version 0.
+    #[cfg_attr(feature = "serde", serde(default))]
+    format_version: u32,
     pub organisms: Vec<organism::Organism>,
     pub size: Vector2<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub food: Vec<Food>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub boundary_policy: BoundaryPolicy,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub predation_enabled: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub config: WorldConfig,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_population: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cull_policy: CullPolicy,
+    // Energy available in each cell, row by row.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub energy_field: Vec<f64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    spatial_grid: SpatialGrid,
+    // Replaces the built-in mutation for every organism and offspring when
+    // set. Operators are code, so they are not saved with the world.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub mutator: Option<Box<dyn Mutator + Send + Sync>>,
+    // Position of each id in `organisms`, refreshed with the spatial grid.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    index: HashMap<isize, usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    lineage: HashMap<isize, isize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    tick: u64,
+    // Organisms that died during the most recent step.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dead: Vec<organism::Organism>,
+    // Seeds each organism's random stream together with its id.
+    #[cfg_attr(feature = "serde", serde(default))]
+    seed: u64,
+    rng: Pcg64,
+    ids: IdAllocator,
+}
+
+#[derive(Debug)]
+pub enum WorldError {
+    Io(io::Error),
+    Deserialize(String),
+    OutOfBounds { id: isize, location: Vector2<isize> },
+    Occupied { id: isize, location: Vector2<isize> },
+    Organism { id: isize, error: OrganismError },
+    VersionMismatch { found: u32, expected: u32 },
+}
+
+impl fmt::Display for WorldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorldError::Io(err) => write!(f, "world i/o failed: {}", err),
+            WorldError::Deserialize(msg) => write!(f, "could not decode world: {}", msg),
+            WorldError::OutOfBounds { id, location } => write!(
+                f,
+                "organism {} is out of bounds at ({}, {})",
+                id, location.x, location.y
+            ),
+            WorldError::Occupied { id, location } => write!(
+                f,
+                "organism {} would overlap another organism at ({}, {})",
+                id, location.x, location.y
+            ),
+            WorldError::Organism { id, error } => write!(f, "organism {}: {}", id, error),
+            WorldError::VersionMismatch { found, expected } => write!(
+                f,
+                "world was saved in format version {}, but version {} is expected",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for WorldError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WorldError::Io(err) => Some(err),
+            WorldError::Organism { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for WorldError {
+    fn from(err: io::Error) -> Self {
+        WorldError::Io(err)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct StepSummary {
+    pub births: usize,
+    pub deaths: usize,
+    pub surviving: usize,
+}
+
+impl World {
+    pub fn new(size: Vector2<usize>) -> Self {
+        Self::from_seed(size, Pcg64::from_entropy().gen())
+    }
+
+    pub fn from_seed(size: Vector2<usize>, seed: u64) -> Self {
+        Self {
+            format_version: CURRENT_FORMAT_VERSION,
+            organisms: Vec::new(),
+            size,
+            food: Vec::new(),
+            boundary_policy: BoundaryPolicy::default(),
+            predation_enabled: false,
+            config: WorldConfig::default(),
+            max_population: None,
+            cull_policy: CullPolicy::default(),
+            energy_field: vec![0.0; size.x * size.y],
+            spatial_grid: SpatialGrid::default(),
+            mutator: None,
+            index: HashMap::new(),
+            lineage: HashMap::new(),
+            tick: 0,
+            dead: Vec::new(),
+            seed,
+            rng: Pcg64::seed_from_u64(seed),
+            ids: IdAllocator::default(),
+        }
+    }
+
+    #[cfg(feature = "ron")]
+    pub fn from_config(path: &Path) -> Result<World, ConfigError> {
+        World::from_scenario(&ScenarioConfig::load(path)?)
+    }
+
+    // Seeds every template's organisms at random in-bounds locations.
+    pub fn from_scenario(config: &ScenarioConfig) -> Result<World, ConfigError> {
+        config.validate()?;
+        let size = Vector2::new(config.size.0, config.size.1);
+        let mut world = match config.seed {
+            Some(seed) => World::from_seed(size, seed),
+            None => World::new(size),
+        };
+        world.config = config.world.clone();
+        for (template, entry) in config.templates.iter().enumerate() {
+            for _ in 0..entry.count {
+                let id = world.allocate_id();
+                let mut organism = organism::Organism::from_template(
+                    id,
+                    entry.genome.clone(),
+                    &world.config.default_attributes,
+                )
+                .map_err(|error| ConfigError::InvalidGenome { template, error })?;
+                organism.reseed(world.seed);
+                organism.location = Vector2::new(
+                    world.rng.gen_range(0..size.x) as isize,
+                    world.rng.gen_range(0..size.y) as isize,
+                );
+                world.organisms.push(organism);
+            }
+        }
+        world.rebuild_spatial_grid();
+        Ok(world)
+    }
+
+    // Save format this world is written in.
+    pub fn format_version(&self) -> u32 {
+        self.format_version
+    }
+
+    // Number of steps the world has run.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    // Hands over the organisms that died during the last step, so callers can
+    // archive them. Each step forgets the dead of the one before it.
+    pub fn remove_dead(&mut self) -> Vec<organism::Organism> {
+        std::mem::take(&mut self.dead)
+    }
+
+    pub fn allocate_id(&mut self) -> isize {
+        self.ids.allocate()
+    }
+
+    // Places a new organism grown from `genome` at `location` and returns its
+    // id. Nothing is added, and no id is used up, if the location is off the
+    // grid or the new body would overlap an existing organism.
+    pub fn spawn(
+        &mut self,
+        genome: organism::Genome,
+        location: Vector2<isize>,
+    ) -> Result<isize, WorldError> {
+        if let Some(max_id) = self.organisms.iter().map(|organism| organism.id).max() {
+            self.ids.reserve_past(max_id);
+        }
+        let id = self.ids.peek();
+        let mut organism =
+            organism::Organism::from_template(id, genome, &self.config.default_attributes)
+                .map_err(|error| WorldError::Organism { id, error })?;
+        organism.reseed(self.seed);
+        if !self.in_bounds(location) {
+            return Err(WorldError::OutOfBounds { id, location });
+        }
+        organism.location = location;
+        let occupied = self.occupied_cells();
+        if organism
+            .occupied_cells()
+            .iter()
+            .any(|cell| occupied.contains(cell))
+        {
+            return Err(WorldError::Occupied { id, location });
+        }
+        self.ids.allocate();
+        self.organisms.push(organism);
+        self.rebuild_spatial_grid();
+        Ok(id)
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn save_to_path(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.flush()
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn load_from_path(path: &Path) -> Result<World, WorldError> {
+        let json = std::fs::read_to_string(path)?;
+        let decode_error = |err: serde_json::Error| WorldError::Deserialize(err.to_string());
+        // The version is checked before decoding the world, so a newer layout
+        // is reported as such instead of as a confusing parse error.
+        let probe: VersionProbe = serde_json::from_str(&json).map_err(decode_error)?;
+        check_format_version(probe.format_version)?;
+        let world: World = serde_json::from_str(&json).map_err(decode_error)?;
+        world.prepare_loaded()
+    }
+
+    // Binary checkpoints share the serde derives with JSON, but are much
+    // smaller and faster to write for large worlds.
+    #[cfg(feature = "bincode")]
+    pub fn save_binary(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        bincode::serialize_into(&mut writer, self).map_err(io::Error::other)?;
+        writer.flush()
+    }
+
+    #[cfg(feature = "bincode")]
+    pub fn load_binary(path: &Path) -> Result<World, WorldError> {
+        let bytes = std::fs::read(path)?;
+        let decode_error = |err: bincode::Error| WorldError::Deserialize(err.to_string());
+        // Binary checkpoints are newer than versioning, so they always start
+        // with a version.
+        let version: u32 = bincode::deserialize(&bytes).map_err(decode_error)?;
+        if version != CURRENT_FORMAT_VERSION {
+            return Err(WorldError::VersionMismatch {
+                found: version,
+                expected: CURRENT_FORMAT_VERSION,
+            });
+        }
+        let world: World = bincode::deserialize(&bytes).map_err(decode_error)?;
+        world.prepare_loaded()
+    }
+
+    #[cfg(feature = "serde")]
+    fn prepare_loaded(mut self) -> Result<World, WorldError> {
+        self.validate_locations()?;
+        // Older versions need nothing beyond the defaults below, so they are
+        // upgraded in place and saved as the current version.
+        self.format_version = CURRENT_FORMAT_VERSION;
+        // Worlds saved before the energy field existed start with an empty one.
+        self.energy_field.resize(self.size.x * self.size.y, 0.0);
+        self.rebuild_spatial_grid();
+        Ok(self)
+    }
+
+    pub fn validate_locations(&self) -> Result<(), WorldError> {
+        match self
+            .organisms
+            .iter()
+            .find(|organism| !self.in_bounds(organism.location))
+        {
+            Some(organism) => Err(WorldError::OutOfBounds {
+                id: organism.id,
+                location: organism.location,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    pub fn spawn_food(&mut self, count: usize, rng: &mut impl Rng) {
+        if self.size.x == 0 || self.size.y == 0 {
+            return;
+        }
+        for _ in 0..count {
+            let position = Vector2::new(
+                rng.gen_range(0..self.size.x) as isize,
+                rng.gen_range(0..self.size.y) as isize,
+            );
+            self.food.push(Food {
+                position,
+                energy: FOOD_ENERGY,
+            });
+        }
+    }
+
+    fn feed(&mut self, organism: &mut organism::Organism) {
+        for (x, y) in organism.occupied_cells() {
+            while let Some(index) = self
+                .food
+                .iter()
+                .position(|food| food.position == Vector2::new(x, y))
+            {
+                let food = self.food.swap_remove(index);
+                organism.gain_energy(food.energy);
+            }
+            // Cells only give up the whole units the organism can absorb.
+            // Energy above the organism's cap is shed first, so it is not
+            // mistaken for energy handed back to the cell.
+            if let Some(index) = field::cell_index(self.size, (x, y)) {
+                if let Some(available) = self.energy_field.get_mut(index) {
+                    organism.gain_energy(0);
+                    let before = organism.energy;
+                    organism.gain_energy(available.max(0.0).floor() as isize);
+                    *available -= (organism.energy - before).max(0) as f64;
+                }
+            }
+        }
+    }
+
+    // Spreads the energy field by one step. Only a wrapping boundary lets
+    // energy flow across the edges of the grid.
+    pub fn diffuse(&mut self) {
+        let wrap = self.boundary_policy == BoundaryPolicy::Wrap;
+        field::diffuse(&mut self.energy_field, self.size, wrap);
+    }
+
+    // The grid is refreshed by every step; call this after moving organisms
+    // around by hand.
+    pub fn rebuild_spatial_grid(&mut self) {
+        self.spatial_grid.rebuild(&self.organisms);
+        self.index = self
+            .organisms
+            .iter()
+            .enumerate()
+            .map(|(index, organism)| (organism.id, index))
+            .collect();
+    }
+
+    // Looks an organism up by id, which stays valid across steps while
+    // positions in `organisms` shift as others die.
+    pub fn get(&self, id: isize) -> Option<&organism::Organism> {
+        self.position_of(id).map(|index| &self.organisms[index])
+    }
+
+    pub fn get_mut(&mut self, id: isize) -> Option<&mut organism::Organism> {
+        self.position_of(id).map(|index| &mut self.organisms[index])
+    }
+
+    // The index is trusted only if it still points at the right organism, so
+    // organisms pushed or reordered by hand are found by a scan instead.
+    fn position_of(&self, id: isize) -> Option<usize> {
+        self.index
+            .get(&id)
+            .copied()
+            .filter(|&index| {
+                self.organisms
+                    .get(index)
+                    .is_some_and(|organism| organism.id == id)
+            })
+            .or_else(|| self.organisms.iter().position(|organism| organism.id == id))
+    }
+
+    pub fn spatial_grid(&self) -> &SpatialGrid {
+        &self.spatial_grid
+    }
+
+    pub fn neighbors_within(&self, pos: Vector2<isize>, radius: isize) -> Vec<usize> {
+        self.spatial_grid.neighbors_within(pos, radius)
+    }
+
+    // Id of the organism closest to `pos`, other than `exclude`, and its
+    // euclidean distance rounded to the nearest cell.
+    pub fn nearest_organism(
+        &self,
+        pos: Vector2<isize>,
+        exclude: Option<isize>,
+    ) -> Option<(isize, isize)> {
+        let (index, squared) = self
+            .spatial_grid
+            .nearest(pos, |index| Some(self.organisms[index].id) == exclude)?;
+        let distance = (squared as f64).sqrt().round() as isize;
+        Some((self.organisms[index].id, distance))
+    }
+
+    // Ancestors of `id`, nearest first, ending with the founder. Parents are
+    // remembered after they die; organisms added by hand fall back to their
+    // own parent_id.
+    pub fn lineage_of(&self, id: isize) -> Vec<isize> {
+        let mut ancestors = Vec::new();
+        let mut visited = HashSet::from([id]);
+        let mut current = id;
+        while let Some(parent) = self.parent_of(current) {
+            if !visited.insert(parent) {
+                break;
+            }
+            ancestors.push(parent);
+            current = parent;
+        }
+        ancestors
+    }
+
+    fn parent_of(&self, id: isize) -> Option<isize> {
+        self.lineage
+            .get(&id)
+            .copied()
+            .or_else(|| self.get(id).and_then(|organism| organism.parent_id))
+    }
+
+    pub fn organisms_where<'a>(
+        &'a self,
+        pred: impl Fn(&organism::Organism) -> bool + 'a,
+    ) -> impl Iterator<Item = &'a organism::Organism> + 'a {
+        self.organisms.iter().filter(move |organism| pred(organism))
+    }
+
+    pub fn oldest(&self) -> Option<&organism::Organism> {
+        self.organisms.iter().max_by_key(|organism| organism.age)
+    }
+
+    pub fn most_energetic(&self) -> Option<&organism::Organism> {
+        self.organisms.iter().max_by_key(|organism| organism.energy)
+    }
+
+    // Runs `f` over every organism, for custom per-tick effects between steps.
+    // The spatial grid is rebuilt afterwards in case `f` moved anything.
+    pub fn apply(&mut self, mut f: impl FnMut(&mut organism::Organism)) {
+        for organism in &mut self.organisms {
+            f(organism);
+        }
+        self.rebuild_spatial_grid();
+    }
+
+    // Energy held by organisms, uneaten food and the energy field. The field
+    // total is rounded to the nearest unit; see field_energy for the exact sum.
+    pub fn total_energy(&self) -> isize {
+        let organisms: isize = self.organisms.iter().map(|organism| organism.energy).sum();
+        let food: isize = self.food.iter().map(|food| food.energy).sum();
+        organisms + food + self.field_energy().round() as isize
+    }
+
+    // Energy left in the field, fractions included.
+    pub fn field_energy(&self) -> f64 {
+        self.energy_field.iter().sum()
+    }
+
+    // Genome of the living organism with the highest score, for reseeding
+    // another run. Organism::fitness is a reasonable default score. Ties go to
+    // whoever comes first in the population.
+    pub fn fittest_genome(
+        &self,
+        score: impl Fn(&organism::Organism) -> f64,
+    ) -> Option<organism::Genome> {
+        self.organisms
+            .iter()
+            .filter(|organism| organism.is_alive())
+            .map(|organism| (score(organism), organism))
+            .rev()
+            .max_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, organism)| organism.genome.clone())
+    }
+
+    // Starts a new generation: the `elite_count` best living organisms by
+    // `score` carry over unchanged, and the rest of the previous population
+    // size is filled with their mutated offspring, each from a random elite.
+    // Everyone else is dropped. Slots whose birth fails stay empty, and with no
+    // elites the world ends up empty.
+    pub fn next_generation(
+        &mut self,
+        elite_count: usize,
+        score: impl Fn(&organism::Organism) -> f64,
+        rng: &mut impl Rng,
+    ) {
+        let population = self.organisms.len();
+        if let Some(max_id) = self.organisms.iter().map(|organism| organism.id).max() {
+            self.ids.reserve_past(max_id);
+        }
+        let mut ranked: Vec<(f64, organism::Organism)> = std::mem::take(&mut self.organisms)
+            .into_iter()
+            .filter(|organism| organism.is_alive())
+            .map(|organism| (score(&organism), organism))
+            .collect();
+        // The sort is stable, so ties keep population order.
+        ranked.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        let elites: Vec<organism::Organism> = ranked
+            .into_iter()
+            .take(elite_count)
+            .map(|(_, organism)| organism)
+            .collect();
+        let mut next = elites.clone();
+        let open_slots = if elites.is_empty() {
+            0
+        } else {
+            population.saturating_sub(elites.len())
+        };
+        // Children are placed the same way step places newborns, so nobody
+        // shares a cell with an elite or a sibling.
+        let mut occupancy = HashMap::new();
+        for elite in &elites {
+            occupy(&mut occupancy, &elite.occupied_cells());
+        }
+        for _ in 0..open_slots {
+            let parent = &elites[rng.gen_range(0..elites.len())];
+            let Ok(mut child) = parent.reproduce_using(self.ids.peek(), rng, self.mutator()) else {
+                continue;
+            };
+            if self
+                .place_newborn(&mut child, parent.location, &occupancy)
+                .is_err()
+            {
+                continue;
+            }
+            self.ids.allocate();
+            occupy(&mut occupancy, &child.occupied_cells());
+            self.lineage.insert(child.id, parent.id);
+            next.push(child);
+        }
+        self.organisms = next;
+        self.rebuild_spatial_grid();
+    }
+
+    pub fn stats(&self) -> PopulationStats {
+        PopulationStats::from_organisms(&self.organisms)
+    }
+
+    #[cfg(feature = "rayon")]
+    pub fn stats_parallel(&self) -> PopulationStats {
+        PopulationStats::from_organisms_parallel(&self.organisms)
+    }
+
+    pub fn snapshot(&self) -> WorldSnapshot {
+        WorldSnapshot::of(self)
+    }
+
+    pub fn histogram(&self, attr: AttrSelector, bins: usize) -> Vec<usize> {
+        stats::histogram(&self.organisms, attr, bins)
+    }
+
+    pub fn density_grid(&self, cell: usize) -> Vec<Vec<usize>> {
+        stats::density_grid(&self.organisms, self.size, cell)
+    }
+
+    // Each organism joins the first species whose members are all within
+    // `threshold` of it, otherwise it founds a new one. Species hold indices
+    // into `organisms`.
+    pub fn cluster_species(&self, threshold: f64) -> Vec<Vec<usize>> {
+        let mut species: Vec<Vec<usize>> = Vec::new();
+        for (index, organism) in self.organisms.iter().enumerate() {
+            let home = species.iter_mut().find(|members| {
+                members.iter().all(|&member| {
+                    self.organisms[member].genome.distance(&organism.genome) < threshold
+                })
+            });
+            match home {
+                Some(members) => members.push(index),
+                None => species.push(vec![index]),
+            }
+        }
+        species
+    }
+
+    pub fn to_svg(&self) -> String {
+        render::to_svg(self)
+    }
+
+    pub fn rasterize(&self, width: usize, height: usize) -> Vec<u8> {
+        render::rasterize(self, width, height)
+    }
+
+    pub fn occupied_cells(&self) -> HashSet<(isize, isize)> {
+        self.organisms
+            .iter()
+            .flat_map(|organism| organism.occupied_cells())
+            .collect()
+    }
+
+    // Id of the organism whose body covers `cell`. Where bodies overlap, the
+    // one that comes first in the population wins.
+    pub fn organism_at(&self, cell: Vector2<isize>) -> Option<isize> {
+        self.organisms
+            .iter()
+            .find(|organism| organism.occupied_cells().contains(&(cell.x, cell.y)))
+            .map(|organism| organism.id)
+    }
+
+    fn ids_are_unique(&self) -> bool {
+        let mut seen = HashSet::with_capacity(self.organisms.len());
+        self.organisms
+            .iter()
+            .all(|organism| seen.insert(organism.id))
+    }
+
+    pub fn in_bounds(&self, location: Vector2<isize>) -> bool {
+        location.x >= 0
+            && location.y >= 0
+            && (location.x as usize) < self.size.x
+            && (location.y as usize) < self.size.y
+    }
+
+    // Maps a location onto the grid according to the boundary policy, or
+    // returns None when the policy kills whatever ends up there.
+    pub fn bounded_location(&self, location: Vector2<isize>) -> Option<Vector2<isize>> {
+        if self.in_bounds(location) {
+            return Some(location);
+        }
+        let width = self.size.x as isize;
+        let height = self.size.y as isize;
+        match self.boundary_policy {
+            BoundaryPolicy::Clamp => Some(Vector2::new(
+                location.x.min(width - 1).max(0),
+                location.y.min(height - 1).max(0),
+            )),
+            BoundaryPolicy::Wrap if width > 0 && height > 0 => Some(Vector2::new(
+                location.x.rem_euclid(width),
+                location.y.rem_euclid(height),
+            )),
+            BoundaryPolicy::Wrap | BoundaryPolicy::Kill => None,
+        }
+    }
+
+    // Where a newborn can go: the spot it was dropped on, or else the first
+    // cell of the ring around its parent, as long as the boundary policy keeps
+    // it on the grid and its body lands on free cells. Fails when the parent is
+    // boxed in, in which case the birth does not happen.
+    fn place_newborn(
+        &self,
+        child: &mut organism::Organism,
+        parent_location: Vector2<isize>,
+        occupancy: &HashMap<(isize, isize), usize>,
+    ) -> Result<Vector2<isize>, ReproductionError> {
+        let ring = PLACEMENT_RING
+            .iter()
+            .map(|&(dx, dy)| parent_location + Vector2::new(dx, dy));
+        for candidate in std::iter::once(child.location).chain(ring) {
+            let Some(location) = self.bounded_location(candidate) else {
+                continue;
+            };
+            child.location = location;
+            if !child
+                .occupied_cells()
+                .iter()
+                .any(|cell| occupancy.contains_key(cell))
+            {
+                return Ok(location);
+            }
+        }
+        Err(ReproductionError::NoValidPlacement)
+    }
+
+    fn mutator(&self) -> &(dyn Mutator + Send + Sync) {
+        self.mutator.as_deref().unwrap_or(&DefaultMutator)
+    }
+
+    // Each organism draws from its own stream, so the outcome does not depend
+    // on how frames are scheduled across threads. Newborns get their real ids
+    // later, when frames are resolved in order.
+    fn run_frames(
+        &self,
+        organisms: &mut [organism::Organism],
+    ) -> Result<Vec<Frame>, OrganismError> {
+        let offspring_id = self.ids.peek();
+        let mutator = self.mutator();
+        let run = |organism: &mut organism::Organism| organism.step_using(offspring_id, mutator);
+        #[cfg(feature = "rayon")]
+        let frames = organisms.par_iter_mut().map(run).collect();
+        #[cfg(not(feature = "rayon"))]
+        let frames = organisms.iter_mut().map(run).collect();
+        frames
+    }
+
+    // Fails without touching the world if any organism is in a state it cannot
+    // be stepped from.
+    pub fn step(&mut self) -> Result<StepSummary, WorldError> {
+        self.step_with_observer(&mut NoopObserver)
+    }
+
+    pub fn step_with_observer(
+        &mut self,
+        observer: &mut impl SimObserver,
+    ) -> Result<StepSummary, WorldError> {
+        for organism in &self.organisms {
+            if let Err(error) = organism.current_body_blueprint() {
+                return Err(WorldError::Organism {
+                    id: organism.id,
+                    error,
+                });
+            }
+        }
+        let mut summary = StepSummary::default();
+        self.dead.clear();
+        // Organisms may have been pushed by hand with their own ids.
+        if let Some(max_id) = self.organisms.iter().map(|organism| organism.id).max() {
+            self.ids.reserve_past(max_id);
+        }
+        let first_newborn_id = self.ids.peek();
+        // Take the population out so organisms can be stepped mutably while
+        // survivors and newborns are collected into fresh vectors.
+        let mut organisms = std::mem::take(&mut self.organisms);
+        let mut survivors = Vec::with_capacity(organisms.len());
+        let mut newborns = Vec::new();
+        // Cell occupancy is counted rather than stored as a set, because
+        // organisms placed by hand may already overlap one another.
+        let mut occupancy = HashMap::new();
+        let previous: Vec<_> = organisms
+            .iter()
+            .map(|organism| {
+                (
+                    organism.location,
+                    organism.body_squares.clone(),
+                    organism.occupied_cells(),
+                )
+            })
+            .collect();
+        for (_, _, cells) in &previous {
+            occupy(&mut occupancy, cells);
+        }
+
+        let frames = self
+            .run_frames(&mut organisms)
+            .expect("body states were validated before stepping");
+
+        for ((mut organism, (mut state, offspring)), (previous_location, previous_body, cells)) in
+            organisms.into_iter().zip(frames).zip(previous)
+        {
+            vacate(&mut occupancy, &cells);
+            match self.bounded_location(organism.location) {
+                Some(location) => organism.location = location,
+                None => {
+                    if state == OrganismState::Alive {
+                        state = OrganismState::Dead(DeathCause::OutOfBounds);
+                    }
+                }
+            }
+            if organism
+                .occupied_cells()
+                .iter()
+                .any(|cell| occupancy.contains_key(cell))
+            {
+                // Both the move and the shape change are undone. The old cells
+                // are still free, as everyone placed so far avoided them.
+                organism.location = previous_location;
+                organism.body_squares = previous_body;
+            }
+            let parent_location = organism.location;
+            if state == OrganismState::Alive {
+                occupy(&mut occupancy, &organism.occupied_cells());
+            }
+            // A parent that left the grid under BoundaryPolicy::Kill was not
+            // there to give birth.
+            let offspring =
+                offspring.filter(|_| state != OrganismState::Dead(DeathCause::OutOfBounds));
+            if let Some(mut child) = offspring {
+                match self.place_newborn(&mut child, parent_location, &occupancy) {
+                    Ok(location) => {
+                        child.location = location;
+                        child.id = self.ids.allocate();
+                        if let Some(parent_id) = child.parent_id {
+                            self.lineage.insert(child.id, parent_id);
+                        }
+                        occupy(&mut occupancy, &child.occupied_cells());
+                        log::debug!(
+                            "tick {}: organism {} born to {:?} at ({}, {})",
+                            self.tick,
+                            child.id,
+                            child.parent_id,
+                            location.x,
+                            location.y
+                        );
+                        observer.on_birth(&child);
+                        newborns.push(child);
+                    }
+                    Err(error) => {
+                        // The parent was charged for the child in next_frame,
+                        // so it gets the energy back when nothing is born.
+                        organism.energy += child.energy;
+                        log::debug!(
+                            "tick {}: birth around ({}, {}) aborted: {}",
+                            self.tick,
+                            parent_location.x,
+                            parent_location.y,
+                            error
+                        );
+                    }
+                }
+            }
+            match state {
+                OrganismState::Alive => {
+                    self.feed(&mut organism);
+                    survivors.push(organism);
+                }
+                OrganismState::Dead(cause) => {
+                    log::debug!(
+                        "tick {}: organism {} died: {:?}",
+                        self.tick,
+                        organism.id,
+                        cause
+                    );
+                    observer.on_death(organism.id);
+                    summary.deaths += 1;
+                    self.dead.push(organism);
+                }
+            }
+        }
+
+        // Newborns only join after predation, so a parent cannot eat a child
+        // born on the same frame.
+        if self.predation_enabled {
+            for prey in resolve_predation(&mut survivors) {
+                log::debug!(
+                    "tick {}: organism {} died: {:?}",
+                    self.tick,
+                    prey.id,
+                    DeathCause::Predation
+                );
+                observer.on_death(prey.id);
+                summary.deaths += 1;
+                self.dead.push(prey);
+            }
+        }
+        summary.surviving = survivors.len();
+        summary.births = newborns.len();
+        survivors.extend(newborns);
+        if let Some(max_population) = self.max_population {
+            // Culled newborns still count as births; culled survivors no
+            // longer count as surviving.
+            for culled in cull(&mut survivors, max_population, self.cull_policy) {
+                log::debug!("tick {}: organism {} culled", self.tick, culled.id);
+                observer.on_death(culled.id);
+                summary.deaths += 1;
+                if culled.id < first_newborn_id {
+                    summary.surviving -= 1;
+                }
+                self.dead.push(culled);
+            }
+        }
+        self.organisms = survivors;
+        self.rebuild_spatial_grid();
+        self.diffuse();
+        self.tick += 1;
+        debug_assert!(self.ids_are_unique(), "organism ids must be unique");
+        Ok(summary)
+    }
+}
+
+// Just the version of a saved world, ignoring everything else.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct VersionProbe {
+    #[serde(default)]
+    format_version: u32,
+}
+
+// Versions up to the current one can be read; version 0 is a world saved
+// before versioning.
+#[cfg(feature = "serde")]
+fn check_format_version(version: u32) -> Result<(), WorldError> {
+    if version > CURRENT_FORMAT_VERSION {
+        return Err(WorldError::VersionMismatch {
+            found: version,
+            expected: CURRENT_FORMAT_VERSION,
+        });
+    }
+    Ok(())
+}
+
+// Removes the weakest organisms until at most `max_population` remain, and
+// returns them. Survivors keep their order, and ties go against whoever comes
+// first in the population.
+fn cull(
+    organisms: &mut Vec<organism::Organism>,
+    max_population: usize,
+    policy: CullPolicy,
+) -> Vec<organism::Organism> {
+    let excess = organisms.len().saturating_sub(max_population);
+    if excess == 0 {
+        return Vec::new();
+    }
+    let mut weakest: Vec<usize> = (0..organisms.len()).collect();
+    match policy {
+        CullPolicy::LowestEnergy => weakest.sort_by_key(|&index| organisms[index].energy),
+        CullPolicy::Oldest => weakest.sort_by_key(|&index| std::cmp::Reverse(organisms[index].age)),
+    }
+    let mut culled = vec![false; organisms.len()];
+    for &index in &weakest[..excess] {
+        culled[index] = true;
+    }
+    take_marked(organisms, &culled)
+}
+
+// Moves the organisms whose flag is set out of `organisms`, keeping the order
+// of both the removed and the remaining ones.
+fn take_marked(
+    organisms: &mut Vec<organism::Organism>,
+    marked: &[bool],
+) -> Vec<organism::Organism> {
+    let mut removed = Vec::new();
+    let mut kept = Vec::with_capacity(organisms.len());
+    for (organism, &marked) in std::mem::take(organisms).into_iter().zip(marked) {
+        if marked {
+            removed.push(organism);
+        } else {
+            kept.push(organism);
+        }
+    }
+    *organisms = kept;
+    removed
+}
+
+type Frame = (OrganismState, Option<organism::Organism>);
+
+fn occupy(occupancy: &mut HashMap<(isize, isize), usize>, cells: &[(isize, isize)]) {
+    for &cell in cells {
+        *occupancy.entry(cell).or_insert(0) += 1;
+    }
+}
+
+fn vacate(occupancy: &mut HashMap<(isize, isize), usize>, cells: &[(isize, isize)]) {
+    for cell in cells {
+        if let Some(count) = occupancy.get_mut(cell) {
+            *count -= 1;
+            if *count == 0 {
+                occupancy.remove(cell);
+            }
+        }
+    }
+}
+
+// Each organism may eat one smaller organism whose body touches its own. Prey
+// are removed, keeping the order of everyone else, and their ids returned.
+fn resolve_predation(organisms: &mut Vec<organism::Organism>) -> Vec<organism::Organism> {
+    // Organisms are indexed by the cells their bodies cover, so contact is
+    // found wherever the squares sit relative to each organism's location.
+    let cells: Vec<_> = organisms
+        .iter()
+        .map(|organism| organism.occupied_cells())
+        .collect();
+    let mut by_cell: HashMap<(isize, isize), Vec<usize>> = HashMap::new();
+    for (index, covered) in cells.iter().enumerate() {
+        for &cell in covered {
+            by_cell.entry(cell).or_default().push(index);
+        }
+    }
+    let mut eaten = vec![false; organisms.len()];
+    for predator in 0..organisms.len() {
+        if eaten[predator] {
+            continue;
+        }
+        let size = organisms[predator].body_squares.size();
+        let mut touching: Vec<usize> = cells[predator]
+            .iter()
+            .flat_map(|&(x, y)| {
+                (-1..=1).flat_map(move |dx| (-1..=1).map(move |dy| (x + dx, y + dy)))
+            })
+            .filter_map(|cell| by_cell.get(&cell))
+            .flatten()
+            .copied()
+            .collect();
+        // Lowest index first, as in population order.
+        touching.sort_unstable();
+        touching.dedup();
+        let prey = touching.into_iter().find(|&prey| {
+            prey != predator && !eaten[prey] && organisms[prey].body_squares.size() < size
+        });
+        if let Some(prey) = prey {
+            eaten[prey] = true;
+            let meal = (organisms[prey].energy as f64 * PREDATION_EFFICIENCY) as isize;
+            organisms[predator].gain_energy(meal);
+        }
+    }
+    take_marked(organisms, &eaten)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::organism::{Body, BodySquare, Genome};
+
+    fn dot() -> Genome {
+        let mut body = Body::new();
+        body.add_square(BodySquare {
+            position: Vector2::new(0.0, 0.0),
+        });
+        Genome::builder().body_state(body).build().unwrap()
+    }
+
+    #[test]
+    fn newborns_need_a_free_cell() {
+        let mut world = World::from_seed(Vector2::new(3, 3), 6);
+        for x in 0..3 {
+            for y in 0..3 {
+                world.spawn(dot(), Vector2::new(x, y)).unwrap();
+            }
+        }
+        let mut occupancy = HashMap::new();
+        for organism in &world.organisms {
+            occupy(&mut occupancy, &organism.occupied_cells());
+        }
+        let parent = &world.organisms[4];
+        let mut child = parent.clone_as(9);
+        assert_eq!(
+            world.place_newborn(&mut child, parent.location, &occupancy),
+            Err(ReproductionError::NoValidPlacement)
+        );
+        vacate(&mut occupancy, &world.organisms[0].occupied_cells());
+        assert_eq!(
+            world.place_newborn(&mut child, parent.location, &occupancy),
+            Ok(Vector2::new(0, 0))
+        );
+    }
 }
@@ -0,0 +1,17 @@
+mod common;
+
+use opus::organism::Attribute;
+
+#[test]
+fn builders_only_change_what_they_set() {
+    let custom = Attribute::builder().metabolism(0.3).build();
+    let expected = Attribute {
+        metabolism: 0.3,
+        ..Attribute::default()
+    };
+    assert_eq!(custom, expected);
+
+    let body = common::body(&[(0.0, 0.0)]);
+    let with_body = Attribute::builder().with_body_state(body.clone()).build();
+    assert_eq!(with_body.body_states, vec![body]);
+}
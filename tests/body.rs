@@ -0,0 +1,140 @@
+mod common;
+
+use common::body;
+use nalgebra::Vector2;
+use opus::organism::{Body, BodySquare};
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+#[test]
+fn identical_squares_overlap() {
+    let mut body = body(&[(0.0, 0.0), (1.0, 0.0)]);
+    assert!(!body.has_overlaps());
+    let square = BodySquare {
+        position: Vector2::new(1.0, 0.0),
+    };
+    assert!(!body.try_add_square(square));
+    body.add_square(square);
+    assert!(body.has_overlaps());
+}
+
+#[test]
+fn l_shape_centroid_and_extents() {
+    let body = body(&[(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]);
+    let center = body.center_of_mass().unwrap();
+    assert!((center - Vector2::new(1.0 / 3.0, 1.0 / 3.0)).norm() < 1e-12);
+    assert_eq!(
+        body.bounding_box(),
+        Some((Vector2::new(0.0, 0.0), Vector2::new(1.0, 1.0)))
+    );
+    assert_eq!(common::body(&[]).center_of_mass(), None);
+}
+
+// Square positions in sorted order, as transforms may reorder squares.
+fn positions(body: &Body) -> Vec<(f64, f64)> {
+    let mut positions: Vec<_> = body
+        .iter()
+        .map(|square| (square.position.x, square.position.y))
+        .collect();
+    positions.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    positions
+}
+
+#[test]
+fn quarter_turns_rotate_an_l_shape_about_its_rounded_centroid() {
+    let l_shape = body(&[(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]);
+    let rotated = l_shape.rotate(1);
+    assert_eq!(positions(&rotated), [(-1.0, 0.0), (0.0, 0.0), (0.0, 1.0)]);
+    assert!(rotated.is_connected());
+    assert_eq!(positions(&l_shape.rotate(4)), positions(&l_shape));
+}
+
+#[test]
+fn reflections_mirror_an_asymmetric_body_about_its_rounded_centroid() {
+    let hook = body(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (2.0, 1.0)]);
+    let mirrored = hook.reflect_x();
+    assert_eq!(
+        positions(&mirrored),
+        [(0.0, 0.0), (0.0, 1.0), (1.0, 0.0), (2.0, 0.0)]
+    );
+    let flipped = hook.reflect_y();
+    assert_eq!(
+        positions(&flipped),
+        [(0.0, 0.0), (1.0, 0.0), (2.0, -1.0), (2.0, 0.0)]
+    );
+    for body in [mirrored, flipped] {
+        assert!(body.is_connected());
+        assert!(!body.has_overlaps());
+    }
+}
+
+#[test]
+fn random_bodies_are_connected_without_overlaps() {
+    let mut rng = Pcg64::seed_from_u64(51);
+    for square_count in 1..=12 {
+        for _ in 0..20 {
+            let body = Body::random(square_count, &mut rng);
+            assert_eq!(body.size(), square_count);
+            assert!(body.is_connected());
+            assert!(!body.has_overlaps());
+        }
+    }
+    assert_eq!(Body::random(0, &mut rng).size(), 0);
+}
+
+#[test]
+fn squares_read_back_in_insertion_order() {
+    let body = body(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]);
+    let squares = body.squares();
+    assert_eq!(squares.len(), 3);
+    assert_eq!(squares[2].position, Vector2::new(1.0, 1.0));
+    assert!(body.iter().eq(squares.iter()));
+}
+
+#[test]
+fn diagonal_squares_touch_only_under_the_default_threshold() {
+    let diagonal = body(&[(0.0, 0.0), (1.0, 1.0)]);
+    assert_eq!(diagonal.adjacency_threshold(), 1.5);
+    assert!(diagonal.is_connected());
+    let orthogonal_only = diagonal.with_adjacency_threshold(1.1);
+    assert!(!orthogonal_only.is_connected());
+    assert!(body(&[(0.0, 0.0), (1.0, 0.0)])
+        .with_adjacency_threshold(1.1)
+        .is_connected());
+}
+
+#[test]
+fn canonical_bodies_compare_equal_whatever_the_build_order() {
+    let mut first = body(&[(1.0, 0.0), (0.0, 1.0), (0.0, 0.0)]);
+    let mut second = body(&[(0.0, 1.0), (0.0, 0.0), (1.0, 0.0)]);
+    assert_ne!(first, second);
+    first.canonicalize();
+    second.canonicalize();
+    assert_eq!(first, second);
+    assert_eq!(first.squares()[0].position, Vector2::new(0.0, 0.0));
+}
+
+#[test]
+fn perimeters_count_unshared_edges() {
+    let block = body(&[(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)]);
+    let line = body(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)]);
+    assert_eq!((block.size(), block.perimeter()), (4, 8));
+    assert_eq!((line.size(), line.perimeter()), (3, 8));
+    assert_eq!(body(&[]).perimeter(), 0);
+}
+
+#[test]
+fn plus_shapes_score_as_more_symmetric_than_l_shapes() {
+    let plus = body(&[(0.0, 0.0), (1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0)]);
+    let l_shape = body(&[(0.0, 0.0), (0.0, 1.0), (0.0, 2.0), (1.0, 0.0)]);
+    assert!((plus.symmetry_score() - 1.0).abs() < 1e-9);
+    assert!(l_shape.symmetry_score() < 1.0);
+    assert!(l_shape.symmetry_score() < plus.symmetry_score());
+}
+
+#[test]
+fn connectivity_of_an_l_shape_two_islands_and_a_single_square() {
+    assert!(body(&[(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]).is_connected());
+    assert!(!body(&[(0.0, 0.0), (3.0, 0.0)]).is_connected());
+    assert!(body(&[(0.0, 0.0)]).is_connected());
+}
@@ -0,0 +1,82 @@
+#![allow(dead_code)]
+
+use opus::organism::{Body, BodySquare, Genome};
+use opus::world::World;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use std::collections::HashMap;
+
+use nalgebra::Vector2;
+
+pub fn body(squares: &[(f64, f64)]) -> Body {
+    let mut body = Body::new();
+    for &(x, y) in squares {
+        body.add_square(BodySquare {
+            position: Vector2::new(x, y),
+        });
+    }
+    body
+}
+
+// A two-state genome whose body alternates between lying and standing.
+pub fn domino() -> Genome {
+    Genome::builder()
+        .body_states(vec![
+            body(&[(0.0, 0.0), (1.0, 0.0)]),
+            body(&[(0.0, 0.0), (0.0, 1.0)]),
+        ])
+        .build()
+        .expect("one gene per attribute")
+}
+
+// A single square that never changes shape, and so never moves.
+pub fn dot() -> Genome {
+    Genome::builder()
+        .body_states(vec![body(&[(0.0, 0.0)])])
+        .build()
+        .expect("one gene per attribute")
+}
+
+// Spawns up to `attempts` random organisms at random free spots.
+pub fn populated_world(size: usize, seed: u64, attempts: usize) -> World {
+    let mut world = World::from_seed(Vector2::new(size, size), seed);
+    let mut rng = Pcg64::seed_from_u64(seed);
+    for _ in 0..attempts {
+        let location = Vector2::new(
+            rng.gen_range(0..size) as isize,
+            rng.gen_range(0..size) as isize,
+        );
+        let genome = Genome::random_from_template(&world.config.default_attributes, &mut rng);
+        let _ = world.spawn(genome, location);
+    }
+    world
+}
+
+// Cells covered by more than one organism.
+pub fn shared_cells(world: &World) -> usize {
+    let mut counts = HashMap::new();
+    for organism in &world.organisms {
+        for cell in organism.occupied_cells() {
+            *counts.entry(cell).or_insert(0) += 1;
+        }
+    }
+    counts.values().filter(|&&count| count > 1).count()
+}
+
+// Id, energy, age and cells of each organism, in population order.
+pub type Fingerprint = Vec<(isize, isize, isize, Vec<(isize, isize)>)>;
+
+pub fn fingerprint(world: &World) -> Fingerprint {
+    world
+        .organisms
+        .iter()
+        .map(|organism| {
+            (
+                organism.id(),
+                organism.energy(),
+                organism.age(),
+                organism.occupied_cells(),
+            )
+        })
+        .collect()
+}
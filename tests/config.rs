@@ -0,0 +1,52 @@
+#![cfg(feature = "ron")]
+
+use opus::world::World;
+
+const SCENARIO: &str = r#"(
+    size: (20, 10),
+    seed: Some(40),
+    templates: [
+        (
+            genome: (
+                genes: [
+                    (
+                        id: 0,
+                        name: "body_states",
+                        value: 0,
+                        attribute_type: BodyStates([
+                            (squares: [(position: (0.0, 0.0))]),
+                        ]),
+                    ),
+                ],
+            ),
+            count: 3,
+        ),
+        (
+            genome: (
+                genes: [
+                    (
+                        id: 0,
+                        name: "body_states",
+                        value: 0,
+                        attribute_type: BodyStates([
+                            (squares: [(position: (0.0, 0.0)), (position: (1.0, 0.0))]),
+                        ]),
+                    ),
+                ],
+            ),
+            count: 2,
+        ),
+    ],
+)"#;
+
+#[test]
+fn scenarios_seed_every_template() {
+    let path = std::env::temp_dir().join(format!("opus-{}-scenario.ron", std::process::id()));
+    std::fs::write(&path, SCENARIO).unwrap();
+    let world = World::from_config(&path);
+    std::fs::remove_file(&path).unwrap();
+    let world = world.unwrap();
+    assert_eq!(world.size.x, 20);
+    assert_eq!(world.size.y, 10);
+    assert_eq!(world.organisms.len(), 5);
+}
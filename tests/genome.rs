@@ -0,0 +1,219 @@
+mod common;
+
+use opus::organism::{
+    Attribute, AttributeType, ExpressionCondition, Genome, GenomeError, Organism,
+};
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+#[test]
+fn crossover_mixes_shared_genes_and_keeps_the_rest() {
+    let first = Genome::builder()
+        .max_energy(100)
+        .reproduction_rate(0.2)
+        .build()
+        .unwrap();
+    let mut second = Genome::builder()
+        .max_age(50)
+        .reproduction_rate(0.6)
+        .build()
+        .unwrap();
+    // Ids 0 and 2 are carried by one parent each; id 1 by both.
+    second.genes[0].id = 2;
+    second.genes[1].id = 1;
+    let mut rng = Pcg64::seed_from_u64(11);
+    let child = first.crossover(&second, &mut rng);
+    let gene = |id: isize| {
+        child
+            .genes
+            .iter()
+            .find(|gene| gene.id == id)
+            .map(|gene| gene.attribute_type.clone())
+    };
+    assert_eq!(child.genes.len(), 3);
+    assert_eq!(gene(0), Some(AttributeType::MaxEnergy(100)));
+    assert_eq!(gene(2), Some(AttributeType::MaxAge(50)));
+    let Some(AttributeType::ReproductionRate(rate)) = gene(1) else {
+        panic!("the shared gene is missing");
+    };
+    assert!((rate - 0.4).abs() < 1e-6);
+}
+
+#[test]
+fn built_genomes_shift_organism_attributes() {
+    let genome = Genome::builder()
+        .max_energy(500)
+        .body_state(common::body(&[(0.0, 0.0), (1.0, 0.0)]))
+        .build()
+        .unwrap();
+    assert_eq!(genome.genes.len(), 2);
+    assert_eq!(genome.genes[0].id, 0);
+    assert_eq!(genome.genes[1].id, 1);
+    let defaults = Attribute::default();
+    let organism = Organism::new(0, genome).unwrap();
+    assert_eq!(organism.attributes().max_energy, defaults.max_energy + 500);
+    assert_eq!(organism.body_squares().size(), 2);
+    assert_eq!(organism.attributes().metabolism, defaults.metabolism);
+}
+
+#[test]
+fn builders_reject_duplicate_attributes_unless_allowed() {
+    let duplicated = || Genome::builder().max_energy(1).max_energy(2);
+    assert_eq!(
+        duplicated().build().unwrap_err(),
+        GenomeError::DuplicateAttribute("max_energy")
+    );
+    assert!(duplicated().allow_duplicates().build().is_ok());
+}
+
+#[test]
+fn competing_genes_contribute_by_weight() {
+    let mut genome = Genome::builder()
+        .allow_duplicates()
+        .max_energy(100)
+        .max_energy(500)
+        .body_state(common::body(&[(0.0, 0.0)]))
+        .build()
+        .unwrap();
+    genome.genes[0].weight = 0.2;
+    genome.genes[1].weight = 0.8;
+    let organism = Organism::new(0, genome.clone()).unwrap();
+    let defaults = Attribute::default();
+    assert_eq!(organism.attributes().max_energy, defaults.max_energy + 420);
+    genome.genes[1].weight = 0.0;
+    let organism = Organism::new(0, genome).unwrap();
+    assert_eq!(organism.attributes().max_energy, defaults.max_energy + 20);
+}
+
+#[test]
+fn random_genomes_are_valid_and_constructible() {
+    let mut rng = Pcg64::seed_from_u64(50);
+    for _ in 0..100 {
+        let genome = Genome::random(&mut rng);
+        assert_eq!(genome.validate(), Ok(()));
+        let organism = Organism::new(0, genome).unwrap();
+        let attributes = organism.attributes();
+        assert!(attributes.max_energy > 0);
+        assert!(attributes.max_age > 0);
+        assert!(organism.body_squares().size() as isize <= attributes.max_size);
+        assert!(organism.body_squares().is_connected());
+    }
+}
+
+#[test]
+fn validation_reports_every_problem() {
+    let mut genome = Genome::builder()
+        .max_energy(100)
+        .body_state(common::body(&[]))
+        .build()
+        .unwrap();
+    genome.genes[1].id = genome.genes[0].id;
+    let id = genome.genes[0].id;
+    assert_eq!(
+        genome.validate().unwrap_err(),
+        [
+            GenomeError::DuplicateGeneId(id),
+            GenomeError::EmptyBody { gene: id }
+        ]
+    );
+}
+
+#[test]
+fn knocking_out_a_gene_drops_its_contribution() {
+    let genome = Genome::builder()
+        .metabolism(0.2)
+        .max_age(50)
+        .body_state(common::body(&[(0.0, 0.0)]))
+        .build()
+        .unwrap();
+    let defaults = Attribute::default();
+    let knocked_out = genome.without_gene(0);
+    let organism = Organism::new(0, knocked_out).unwrap();
+    assert_eq!(organism.attributes().metabolism, defaults.metabolism);
+    assert_eq!(organism.attributes().max_age, defaults.max_age + 50);
+    // The source genome keeps the gene.
+    let organism = Organism::new(0, genome.clone()).unwrap();
+    assert!((organism.attributes().metabolism - (defaults.metabolism + 0.2)).abs() < 1e-6);
+
+    let overexpressed = Organism::new(0, genome.with_gene_value(1, 500).unwrap()).unwrap();
+    assert_eq!(overexpressed.attributes().max_age, defaults.max_age + 500);
+    assert_eq!(genome.genes.len(), 3);
+    // Rate genes cannot take an integer value.
+    assert_eq!(
+        genome.with_gene_value(0, 5).unwrap_err(),
+        GenomeError::NotAnIntegerGene {
+            gene: 0,
+            attribute: "metabolism"
+        }
+    );
+}
+
+#[test]
+fn organisms_start_within_their_reduced_max_energy() {
+    let genome = Genome::builder()
+        .max_energy(500 - Attribute::default().max_energy)
+        .body_state(common::body(&[(0.0, 0.0)]))
+        .build()
+        .unwrap();
+    let organism = Organism::new(0, genome.clone()).unwrap();
+    assert_eq!(organism.attributes().max_energy, 500);
+    assert_eq!(organism.energy(), 500);
+
+    let template = Attribute::builder().starting_energy_fraction(0.5).build();
+    let organism = Organism::from_template(0, genome, &template).unwrap();
+    assert_eq!(organism.energy(), 250);
+}
+
+#[test]
+fn lerp_hits_both_endpoints_and_the_midpoint() {
+    let genome = |max_energy: isize, metabolism: f32, tip: (f64, f64)| {
+        Genome::builder()
+            .max_energy(max_energy)
+            .metabolism(metabolism)
+            .body_state(common::body(&[(0.0, 0.0), tip]))
+            .build()
+            .unwrap()
+    };
+    let start = genome(100, 0.1, (1.0, 0.0));
+    let end = genome(300, 0.3, (1.0, 2.0));
+    assert_eq!(start.lerp(&end, 0.0), start);
+    assert_eq!(start.lerp(&end, 1.0), end);
+
+    let middle = start.lerp(&end, 0.5);
+    assert_eq!(
+        middle.genes[0].attribute_type,
+        AttributeType::MaxEnergy(200)
+    );
+    let AttributeType::Metabolism(metabolism) = middle.genes[1].attribute_type else {
+        panic!("the metabolism gene is missing");
+    };
+    assert!((metabolism - 0.2).abs() < 1e-6);
+    let AttributeType::BodyStates(bodies) = &middle.genes[2].attribute_type else {
+        panic!("the body gene is missing");
+    };
+    assert_eq!(bodies[0], common::body(&[(0.0, 0.0), (1.0, 1.0)]));
+}
+
+#[test]
+fn conditional_genes_switch_at_their_energy_threshold() {
+    let template = Attribute::builder()
+        .metabolism(0.1)
+        .mutation_rate(0.0)
+        .build();
+    let genome = Genome::builder()
+        .metabolism(-0.1)
+        .expressed_when(ExpressionCondition::EnergyBelow(500))
+        .body_state(common::body(&[(0.0, 0.0)]))
+        .build()
+        .unwrap();
+    let mut organism = Organism::from_template(0, genome, &template).unwrap();
+    let mut rng = Pcg64::seed_from_u64(82);
+    let mut metabolism_at = |energy: isize| {
+        organism.gain_energy(energy - organism.energy());
+        organism.next_frame(1, &mut rng).unwrap();
+        organism.attributes().metabolism
+    };
+    assert_eq!(metabolism_at(600), 0.1);
+    assert_eq!(metabolism_at(400), 0.0);
+    assert_eq!(metabolism_at(600), 0.1);
+}
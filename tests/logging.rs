@@ -0,0 +1,51 @@
+mod common;
+
+use log::{Level, Log, Metadata, Record};
+use nalgebra::Vector2;
+use opus::organism::Attribute;
+use opus::world::World;
+use std::sync::Mutex;
+
+// Keeps every record so tests can look for the events they expect.
+struct CapturingLogger {
+    records: Mutex<Vec<(Level, String)>>,
+}
+
+impl Log for CapturingLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.records
+            .lock()
+            .unwrap()
+            .push((record.level(), record.args().to_string()));
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CapturingLogger = CapturingLogger {
+    records: Mutex::new(Vec::new()),
+};
+
+#[test]
+fn deaths_are_logged() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+    let mut world = World::from_seed(Vector2::new(5, 5), 18);
+    // Nothing outlives a lifespan of 0.
+    world.config.default_attributes = Attribute::builder().max_age(0).build();
+    let id = world.spawn(common::dot(), Vector2::new(2, 2)).unwrap();
+    assert_eq!(world.step().unwrap().deaths, 1);
+    let expected = format!("organism {} died", id);
+    let records = LOGGER.records.lock().unwrap();
+    assert!(
+        records
+            .iter()
+            .any(|(level, message)| *level == Level::Debug && message.contains(&expected)),
+        "no death among {:?}",
+        records
+    );
+}
@@ -0,0 +1,23 @@
+mod common;
+
+use opus::metrics::{MetricsRecorder, CSV_HEADER};
+
+#[test]
+fn three_ticks_give_a_header_and_three_rows() {
+    let mut world = common::populated_world(20, 41, 20);
+    let mut recorder = MetricsRecorder::new();
+    for _ in 0..3 {
+        world.step().unwrap();
+        recorder.record(&world).unwrap();
+    }
+    let mut csv = Vec::new();
+    recorder.write_to(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0], CSV_HEADER);
+    for (tick, line) in lines[1..].iter().enumerate() {
+        assert!(line.starts_with(&format!("{},", tick + 1)));
+        assert_eq!(line.split(',').count(), 6);
+    }
+}
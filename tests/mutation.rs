@@ -0,0 +1,211 @@
+mod common;
+
+use opus::mutation::{AddSquare, DefaultMutator, Mutator, NoMutation};
+use opus::organism::{Attribute, Genome, Organism};
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+// Energy lost over one frame in which the organism is sure to mutate and
+// nothing else costs anything.
+fn energy_spent_mutating(mutator: &dyn Mutator) -> isize {
+    let mut organism = Organism::new(0, common::domino()).unwrap();
+    organism.update_attributes(|attributes| {
+        attributes.metabolism = 0.0;
+        attributes.movement_cost_factor = 0.0;
+        attributes.transition_cost_factor = 0.0;
+        attributes.reproduction_rate = 0.0;
+        attributes.max_mutation_rate = 1.0;
+        attributes.mutation_rate = 1.0;
+        attributes.mutation_cost = 5.0;
+    });
+    let before = organism.energy();
+    let mut rng = Pcg64::seed_from_u64(12);
+    organism.next_frame_using(1, &mut rng, mutator).unwrap();
+    before - organism.energy()
+}
+
+#[test]
+fn mutation_is_charged_per_square() {
+    // Two body states of two squares each.
+    assert_eq!(energy_spent_mutating(&DefaultMutator), 20);
+}
+
+#[test]
+fn operators_that_change_nothing_cost_nothing() {
+    assert_eq!(energy_spent_mutating(&NoMutation), 0);
+}
+
+#[test]
+fn mutation_grows_and_shrinks_bodies_in_one_piece() {
+    let mut organism = Organism::new(0, common::domino()).unwrap();
+    organism.update_attributes(|attributes| {
+        attributes.max_mutation_rate = 1.0;
+        attributes.mutation_rate = 0.5;
+        attributes.max_size = 10;
+    });
+    let mut rng = Pcg64::seed_from_u64(12);
+    let (mut grew, mut shrank) = (false, false);
+    for _ in 0..500 {
+        let before: Vec<usize> = organism
+            .attributes()
+            .body_states
+            .iter()
+            .map(|body| body.size())
+            .collect();
+        DefaultMutator.mutate(&mut organism, &mut rng);
+        for (body, before) in organism.attributes().body_states.iter().zip(before) {
+            assert!(body.size() > 0);
+            assert!(body.is_connected());
+            grew |= body.size() > before;
+            shrank |= body.size() < before;
+        }
+    }
+    assert!(grew && shrank);
+}
+
+#[test]
+fn metabolism_drifts_across_generations() {
+    let genome = Genome::builder()
+        .metabolism(0.0)
+        .body_state(common::body(&[(0.0, 0.0)]))
+        .build()
+        .unwrap();
+    let mut organism = Organism::new(0, genome).unwrap();
+    organism.update_attributes(|attributes| {
+        attributes.max_mutation_rate = 1.0;
+        attributes.mutation_rate = 0.5;
+    });
+    let start = organism.attributes().metabolism;
+    let mut rng = Pcg64::seed_from_u64(13);
+    for generation in 1..=50 {
+        organism.gain_energy(isize::MAX);
+        if let Ok(child) = organism.reproduce(generation, &mut rng) {
+            organism = child;
+        }
+    }
+    assert!(organism.id() > 0, "no generation was born");
+    assert_ne!(organism.attributes().metabolism, start);
+}
+
+#[test]
+fn mutation_keeps_bodies_within_max_size() {
+    let line = |squares: usize| {
+        let cells: Vec<_> = (0..squares).map(|x| (x as f64, 0.0)).collect();
+        common::body(&cells)
+    };
+    let genome = Genome::builder()
+        .body_states(vec![line(3), line(5)])
+        .build()
+        .unwrap();
+    let mut organism = Organism::new(0, genome).unwrap();
+    organism.update_attributes(|attributes| attributes.max_size = 3);
+    let mut rng = Pcg64::seed_from_u64(37);
+    AddSquare.mutate(&mut organism, &mut rng);
+    assert_eq!(organism.attributes().body_states[0].size(), 3);
+    DefaultMutator.mutate(&mut organism, &mut rng);
+    for body in &organism.attributes().body_states {
+        assert!(body.size() <= 3, "body of {} squares", body.size());
+    }
+}
+
+#[test]
+fn mutation_rate_stays_under_its_cap() {
+    let genome = Genome::builder()
+        .mutation_rate(0.8)
+        .body_state(common::body(&[(0.0, 0.0)]))
+        .build()
+        .unwrap();
+    let mut organism = Organism::new(0, genome).unwrap();
+    organism.update_attributes(|attributes| attributes.max_mutation_rate = 0.3);
+    let mut rng = Pcg64::seed_from_u64(73);
+    for _ in 0..300 {
+        DefaultMutator.mutate(&mut organism, &mut rng);
+        let rate = organism.attributes().mutation_rate;
+        assert!((0.0..=0.3).contains(&rate), "mutation rate {}", rate);
+    }
+
+    // Regression pulls the rate back towards the default baseline.
+    organism.update_attributes(|attributes| attributes.mutation_rate_regression = 0.5);
+    for _ in 0..50 {
+        DefaultMutator.mutate(&mut organism, &mut rng);
+    }
+    let baseline = Attribute::default().mutation_rate;
+    assert!((organism.attributes().mutation_rate - baseline).abs() < 0.05);
+}
+
+#[test]
+fn grid_aligned_bodies_stay_on_lattice_points() {
+    let mut organism = Organism::new(0, common::domino()).unwrap();
+    organism.update_attributes(|attributes| {
+        attributes.grid_aligned = true;
+        attributes.mutation_rate = 0.5;
+        attributes.max_size = 6;
+    });
+    let mut rng = Pcg64::seed_from_u64(85);
+    for _ in 0..1000 {
+        DefaultMutator.mutate(&mut organism, &mut rng);
+    }
+    for body in &organism.attributes().body_states {
+        for square in body.iter() {
+            let offset = square.position - square.position.map(f64::round);
+            assert!(offset.norm() < 1e-9, "square at {:?}", square.position);
+        }
+    }
+}
+
+// A user-defined operator that leaves organisms alone and counts its calls.
+#[derive(Default)]
+struct Untouched {
+    calls: std::cell::Cell<usize>,
+}
+
+impl Mutator for Untouched {
+    fn mutate(&self, _organism: &mut Organism, _rng: &mut dyn rand::RngCore) {
+        self.calls.set(self.calls.get() + 1);
+    }
+}
+
+#[test]
+fn custom_no_op_mutators_give_identical_offspring() {
+    let mut parent = Organism::new(0, common::domino()).unwrap();
+    parent.update_attributes(|attributes| {
+        attributes.metabolism = 0.0;
+        attributes.max_mutation_rate = 1.0;
+        attributes.mutation_rate = 1.0;
+        attributes.reproduction_rate = 1.0;
+        attributes.puberty_age = 0;
+    });
+    let mutator = Untouched::default();
+    let mut rng = Pcg64::seed_from_u64(99);
+    let (_, offspring) = parent.next_frame_using(1, &mut rng, &mutator).unwrap();
+    let child = offspring.expect("the parent reproduces every frame");
+    assert!(mutator.calls.get() > 0);
+    assert_eq!(child.genome(), parent.genome());
+    assert_eq!(
+        child.attributes().body_states,
+        parent.attributes().body_states
+    );
+}
+
+#[test]
+fn attribute_edits_survive_mutation_and_reach_offspring() {
+    let mut organism = Organism::new(0, common::domino()).unwrap();
+    organism.update_attributes(|attributes| {
+        attributes.metabolism = 0.9;
+        attributes.mutation_rate = 1.0;
+        attributes.movement_cost_factor = 3.0;
+    });
+    let mut rng = Pcg64::seed_from_u64(83);
+    DefaultMutator.mutate(&mut organism, &mut rng);
+    assert_eq!(organism.attributes().metabolism, 0.9);
+    assert_eq!(organism.attributes().mutation_rate, 1.0);
+
+    organism.gain_energy(isize::MAX);
+    let partner = Organism::new(1, common::domino()).unwrap();
+    let child = organism
+        .reproduce_with_using(&partner, 2, &mut rng, &NoMutation)
+        .unwrap();
+    assert_eq!(child.attributes().metabolism, 0.9);
+    assert_eq!(child.attributes().mutation_rate, 1.0);
+    assert_eq!(child.attributes().movement_cost_factor, 3.0);
+}
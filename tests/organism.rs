@@ -0,0 +1,538 @@
+mod common;
+
+use nalgebra::Vector2;
+use opus::mutation::NoMutation;
+use opus::organism::{
+    BehaviorMode, CycleMode, DeathCause, Genome, MortalityModel, MovementMode, Organism,
+    OrganismError, OrganismState,
+};
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+use std::collections::HashSet;
+
+#[test]
+fn empty_genomes_are_rejected() {
+    let genome = Genome { genes: Vec::new() };
+    assert_eq!(
+        Organism::new(0, genome).unwrap_err(),
+        OrganismError::EmptyBodyStates
+    );
+}
+
+// An organism that reproduces on every frame and pays for nothing else.
+fn breeder() -> Organism {
+    let mut organism = Organism::new(0, common::domino()).unwrap();
+    organism.update_attributes(|attributes| {
+        attributes.metabolism = 0.0;
+        attributes.movement_cost_factor = 0.0;
+        attributes.transition_cost_factor = 0.0;
+        attributes.mutation_rate = 0.0;
+        attributes.reproduction_rate = 1.0;
+        attributes.puberty_age = 0;
+        attributes.min_reproduction_energy = 0;
+    });
+    organism
+}
+
+#[test]
+fn aborted_reproduction_leaves_the_parent_untouched() {
+    let mut organism = breeder();
+    // Both body states are two squares, so every child is over the cap.
+    organism.update_attributes(|attributes| attributes.max_size = 1);
+    let before = organism.energy();
+    let mut rng = Pcg64::seed_from_u64(14);
+    let (state, offspring) = organism.next_frame_using(1, &mut rng, &NoMutation).unwrap();
+    assert_eq!(state, OrganismState::Alive);
+    assert!(offspring.is_none());
+    assert_eq!(organism.energy(), before);
+}
+
+#[test]
+fn offspring_energy_comes_out_of_the_parent() {
+    for split_fraction in [0.1, 0.5, 0.9] {
+        let mut organism = breeder();
+        organism.update_attributes(|attributes| attributes.split_fraction = split_fraction);
+        let before = organism.energy();
+        let mut rng = Pcg64::seed_from_u64(15);
+        let (_, offspring) = organism.next_frame_using(1, &mut rng, &NoMutation).unwrap();
+        let child = offspring.expect("breeders reproduce every frame");
+        assert_eq!(
+            child.energy(),
+            (before as f64 * split_fraction as f64) as isize
+        );
+        assert_eq!(before, organism.energy() + child.energy());
+    }
+}
+
+#[test]
+fn energy_gains_stop_at_max_energy() {
+    let mut organism = Organism::new(0, common::dot()).unwrap();
+    organism.update_attributes(|attributes| attributes.max_energy = 1000);
+    organism.gain_energy(5000);
+    assert_eq!(organism.energy(), 1000);
+}
+
+#[test]
+fn organisms_do_not_reproduce_before_puberty() {
+    let mut organism = breeder();
+    organism.update_attributes(|attributes| {
+        attributes.puberty_age = 50;
+        attributes.max_age = 1000;
+    });
+    let mut rng = Pcg64::seed_from_u64(16);
+    for frame in 1..50 {
+        let (_, offspring) = organism.next_frame_using(1, &mut rng, &NoMutation).unwrap();
+        assert!(offspring.is_none(), "offspring at age {}", frame);
+    }
+    let (_, offspring) = organism.next_frame_using(1, &mut rng, &NoMutation).unwrap();
+    assert_eq!(organism.age(), 50);
+    assert!(offspring.is_some());
+}
+
+#[test]
+fn rates_and_counts_are_clamped_after_genes_apply() {
+    let genome = Genome::builder()
+        .mutation_rate(-100.0)
+        .reproduction_rate(100.0)
+        .max_age(-1_000_000)
+        .body_state(common::body(&[(0.0, 0.0)]))
+        .build()
+        .unwrap();
+    let organism = Organism::new(0, genome).unwrap();
+    assert_eq!(organism.attributes().mutation_rate, 0.0);
+    assert_eq!(organism.attributes().reproduction_rate, 1.0);
+    assert_eq!(organism.attributes().max_age, 0);
+}
+
+#[test]
+fn stepping_without_body_states_is_an_error() {
+    let mut organism = Organism::new(0, common::dot()).unwrap();
+    organism.update_attributes(|attributes| attributes.body_states.clear());
+    let mut rng = Pcg64::seed_from_u64(28);
+    assert_eq!(
+        organism.next_frame(1, &mut rng).unwrap_err(),
+        OrganismError::EmptyBodyStates
+    );
+}
+
+// An organism that neither mutates nor reproduces and pays nothing per frame,
+// so tests can switch on just the cost they look at.
+fn free_living(genome: Genome) -> Organism {
+    let mut organism = Organism::new(0, genome).unwrap();
+    organism.update_attributes(|attributes| {
+        attributes.metabolism = 0.0;
+        attributes.movement_cost_factor = 0.0;
+        attributes.transition_cost_factor = 0.0;
+        attributes.mutation_rate = 0.0;
+        attributes.reproduction_rate = 0.0;
+        attributes.max_age = 1000;
+    });
+    organism
+}
+
+// Energy spent over `frames` frames.
+fn energy_spent(organism: &mut Organism, frames: usize) -> isize {
+    let before = organism.energy();
+    let mut rng = Pcg64::seed_from_u64(29);
+    for _ in 0..frames {
+        organism.next_frame(1, &mut rng).unwrap();
+    }
+    before - organism.energy()
+}
+
+#[test]
+fn moving_costs_more_than_standing_still() {
+    let moving_cost = |genome: Genome| {
+        let mut organism = free_living(genome);
+        organism.update_attributes(|attributes| attributes.movement_cost_factor = 10.0);
+        energy_spent(&mut organism, 4)
+    };
+    assert_eq!(moving_cost(common::dot()), 0);
+    assert!(moving_cost(common::domino()) > 0);
+}
+
+#[test]
+fn body_states_of_different_sizes_still_move() {
+    let genome = Genome::builder()
+        .body_states(vec![
+            common::body(&[(0.0, 0.0), (1.0, 0.0)]),
+            common::body(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)]),
+        ])
+        .build()
+        .unwrap();
+    let mut organism = free_living(genome);
+    let start = organism.location();
+    let mut rng = Pcg64::seed_from_u64(30);
+    organism.next_frame(1, &mut rng).unwrap();
+    organism.next_frame(1, &mut rng).unwrap();
+    assert_eq!(organism.body_squares().size(), 3);
+    let moved = organism.location() - start;
+    assert_ne!(moved.x, 0);
+    assert_eq!(moved.y, 0);
+}
+
+#[test]
+fn a_movement_scale_of_zero_keeps_organisms_in_place() {
+    let mut organism = free_living(common::domino());
+    organism.update_attributes(|attributes| attributes.movement_scale = 0.0);
+    let start = organism.location();
+    let mut rng = Pcg64::seed_from_u64(31);
+    for _ in 0..6 {
+        organism.next_frame(1, &mut rng).unwrap();
+        assert_eq!(organism.location(), start);
+    }
+}
+
+#[test]
+fn upkeep_grows_faster_than_body_size_with_a_larger_exponent() {
+    let upkeep = |squares: usize, exponent: f32| {
+        let line: Vec<_> = (0..squares).map(|x| (x as f64, 0.0)).collect();
+        let genome = Genome::builder()
+            .body_state(common::body(&line))
+            .build()
+            .unwrap();
+        let mut organism = free_living(genome);
+        organism.update_attributes(|attributes| {
+            attributes.metabolism = 1.0;
+            attributes.metabolism_exponent = exponent;
+            attributes.senescence_factor = 0.0;
+        });
+        energy_spent(&mut organism, 1)
+    };
+    assert_eq!(upkeep(10, 1.0), 5 * upkeep(2, 1.0));
+    // 10^1.5 and 2^1.5, rounded down.
+    assert_eq!(upkeep(2, 1.5), 2);
+    assert_eq!(upkeep(10, 1.5), 31);
+}
+
+// Counts the words drawn from the wrapped generator.
+struct CountingRng {
+    inner: Pcg64,
+    draws: usize,
+}
+
+impl rand::RngCore for CountingRng {
+    fn next_u32(&mut self) -> u32 {
+        self.draws += 1;
+        self.inner.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.draws += 1;
+        self.inner.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.draws += 1;
+        self.inner.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.draws += 1;
+        self.inner.try_fill_bytes(dest)
+    }
+}
+
+#[test]
+fn frames_without_offspring_only_roll_for_mutation_and_reproduction() {
+    let mut organism = free_living(common::domino());
+    organism.update_attributes(|attributes| attributes.puberty_age = 5);
+    let mut rng = CountingRng {
+        inner: Pcg64::seed_from_u64(43),
+        draws: 0,
+    };
+    let mut draws = Vec::new();
+    for _ in 0..6 {
+        let before = rng.draws;
+        let (_, offspring) = organism.next_frame(1, &mut rng).unwrap();
+        assert!(offspring.is_none());
+        draws.push(rng.draws - before);
+    }
+    // The reproduction roll only starts at puberty.
+    assert_eq!(draws, [1, 1, 1, 1, 2, 2]);
+}
+
+#[test]
+fn low_energy_blocks_reproduction_despite_a_certain_roll() {
+    let mut organism = breeder();
+    organism.update_attributes(|attributes| {
+        attributes.min_reproduction_energy = attributes.max_energy;
+        attributes.max_age = 1000;
+    });
+    let mut rng = Pcg64::seed_from_u64(44);
+    for _ in 0..10 {
+        let (_, offspring) = organism.next_frame_using(1, &mut rng, &NoMutation).unwrap();
+        assert!(offspring.is_none());
+    }
+}
+
+// Steps until the organism dies and reports the cause.
+fn cause_of_death(organism: &mut Organism) -> DeathCause {
+    let mut rng = Pcg64::seed_from_u64(48);
+    loop {
+        match organism.next_frame(1, &mut rng).unwrap() {
+            (OrganismState::Dead(cause), _) => return cause,
+            (OrganismState::Alive, _) => assert!(organism.is_alive()),
+        }
+    }
+}
+
+#[test]
+fn starvation_and_old_age_are_told_apart() {
+    let mut starving = free_living(common::dot());
+    starving.update_attributes(|attributes| attributes.metabolism = 100.0);
+    assert_eq!(cause_of_death(&mut starving), DeathCause::Starvation);
+    assert!(!starving.is_alive());
+
+    let mut ageing = free_living(common::dot());
+    ageing.update_attributes(|attributes| attributes.max_age = 5);
+    assert_eq!(cause_of_death(&mut ageing), DeathCause::OldAge);
+    assert_eq!(ageing.age(), 5);
+    assert!(!ageing.is_alive());
+}
+
+#[test]
+fn upkeep_rises_with_age_under_senescence() {
+    let line: Vec<_> = (0..10).map(|x| (x as f64, 0.0)).collect();
+    let genome = Genome::builder()
+        .body_state(common::body(&line))
+        .build()
+        .unwrap();
+    let mut organism = free_living(genome);
+    organism.update_attributes(|attributes| {
+        attributes.metabolism = 1.0;
+        attributes.senescence_factor = 1.0;
+        attributes.max_age = 100;
+        attributes.max_energy = 1_000_000;
+    });
+    organism.gain_energy(1_000_000);
+    let young = energy_spent(&mut organism, 1);
+    energy_spent(&mut organism, 89);
+    let old = energy_spent(&mut organism, 1);
+    assert_eq!(organism.age(), 91);
+    // 10 squares cost 10 at age 0 and 10 * (1 + 90 / 100) at age 90.
+    assert_eq!(young, 10);
+    assert_eq!(old, 19);
+    assert_eq!(organism.attributes().metabolism, 1.0);
+}
+
+#[test]
+fn clones_share_the_genome_under_a_new_id() {
+    let mut source = free_living(common::domino());
+    let mut rng = Pcg64::seed_from_u64(58);
+    for _ in 0..3 {
+        source.next_frame(1, &mut rng).unwrap();
+    }
+    let copy = source.clone_as(7);
+    assert_eq!(copy.id(), 7);
+    assert_eq!(copy.parent_id(), Some(source.id()));
+    assert_eq!(copy.genome(), source.genome());
+    assert_eq!(copy.energy(), source.energy());
+    assert_eq!(copy.age(), source.age());
+    assert_eq!(copy.location(), source.location());
+}
+
+#[test]
+fn shape_changes_cost_per_changed_square() {
+    let transition_cost = |genome: Genome| {
+        let mut organism = free_living(genome);
+        organism.update_attributes(|attributes| attributes.transition_cost_factor = 10.0);
+        energy_spent(&mut organism, 6)
+    };
+    let bar = Genome::builder()
+        .body_states(vec![
+            common::body(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)]),
+            common::body(&[(0.0, 0.0), (0.0, 1.0), (0.0, 2.0)]),
+        ])
+        .build()
+        .unwrap();
+    let still = transition_cost(common::dot());
+    let domino = transition_cost(common::domino());
+    // The bar moves two squares per change, the domino one.
+    assert_eq!(still, 0);
+    assert!(domino > 0);
+    assert_eq!(transition_cost(bar), 2 * domino);
+}
+
+#[test]
+fn offspring_land_on_both_sides_of_the_parent() {
+    let parent = breeder();
+    let mut rng = Pcg64::seed_from_u64(61);
+    let mut offsets = HashSet::new();
+    for id in 1..=200 {
+        let child = parent.reproduce(id, &mut rng).unwrap();
+        let offset = child.location() - parent.location();
+        offsets.insert((offset.x, offset.y));
+    }
+    for axis_offset in [-1, 1] {
+        assert!(offsets.iter().any(|&(x, _)| x == axis_offset));
+        assert!(offsets.iter().any(|&(_, y)| y == axis_offset));
+    }
+    assert!(offsets.iter().all(|&(x, y)| x.abs() <= 1 && y.abs() <= 1));
+}
+
+#[test]
+fn manhattan_movement_takes_single_orthogonal_steps() {
+    let mut organism = free_living(common::domino());
+    organism.update_attributes(|attributes| attributes.movement_mode = MovementMode::Manhattan);
+    let start = organism.location();
+    let mut rng = Pcg64::seed_from_u64(63);
+    for _ in 0..8 {
+        let before = organism.location();
+        organism.next_frame(1, &mut rng).unwrap();
+        let step = organism.location() - before;
+        assert!(step.x.abs() + step.y.abs() <= 1, "moved by {:?}", step);
+    }
+    assert_ne!(organism.location(), start);
+}
+
+#[test]
+fn sprawling_bodies_pay_more_upkeep_than_compact_ones() {
+    let upkeep = |squares: &[(f64, f64)]| {
+        let genome = Genome::builder()
+            .body_state(common::body(squares))
+            .build()
+            .unwrap();
+        let mut organism = free_living(genome);
+        organism.update_attributes(|attributes| {
+            attributes.metabolism = 1.0;
+            attributes.metabolism_exponent = 1.0;
+            attributes.senescence_factor = 0.0;
+            attributes.compactness_factor = 1.0;
+        });
+        energy_spent(&mut organism, 1)
+    };
+    // A diagonal line spans nine cells, a tight cluster four.
+    let line = upkeep(&[(0.0, 0.0), (1.0, 1.0), (2.0, 2.0)]);
+    let cluster = upkeep(&[(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]);
+    assert_eq!(line, 9);
+    assert_eq!(cluster, 4);
+    assert_eq!(upkeep(&[(0.0, 0.0)]), 1);
+}
+
+#[test]
+fn probabilistic_mortality_spreads_deaths_before_max_age() {
+    let mut rng = Pcg64::seed_from_u64(67);
+    let mut ages_at_death = Vec::new();
+    for _ in 0..200 {
+        let mut organism = free_living(common::dot());
+        organism.update_attributes(|attributes| {
+            attributes.max_age = 100;
+            attributes.mortality_model = MortalityModel::Probabilistic;
+        });
+        while let (OrganismState::Alive, _) = organism.next_frame(1, &mut rng).unwrap() {}
+        ages_at_death.push(organism.age());
+    }
+    let survivors_at = |age: isize| ages_at_death.iter().filter(|&&died| died > age).count();
+    assert!(ages_at_death.iter().all(|&age| age <= 100));
+    assert!(survivors_at(99) < 100);
+    assert!(survivors_at(50) > 150);
+    // Deaths per survivor rise with each quarter of life.
+    let hazards: Vec<_> = [0, 25, 50, 75]
+        .iter()
+        .map(|&start| {
+            let at_risk = survivors_at(start) as f64;
+            (at_risk - survivors_at(start + 25) as f64) / at_risk
+        })
+        .collect();
+    assert!(
+        hazards.windows(2).all(|pair| pair[0] < pair[1]),
+        "{:?}",
+        hazards
+    );
+}
+
+#[test]
+fn ping_pong_cycles_back_and_forth() {
+    let genome = Genome::builder()
+        .body_states(vec![
+            common::body(&[(0.0, 0.0)]),
+            common::body(&[(0.0, 0.0), (1.0, 0.0)]),
+            common::body(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)]),
+        ])
+        .build()
+        .unwrap();
+    let mut organism = free_living(genome);
+    organism.update_attributes(|attributes| attributes.cycle_mode = CycleMode::PingPong);
+    let mut rng = Pcg64::seed_from_u64(71);
+    let mut states = vec![organism.current_body_state()];
+    for _ in 0..5 {
+        organism.next_frame(1, &mut rng).unwrap();
+        states.push(organism.current_body_state());
+    }
+    assert_eq!(states, [0, 1, 2, 1, 0, 1]);
+
+    assert!(organism.set_body_state(2).is_ok());
+    assert_eq!(organism.current_body_state(), 2);
+    assert_eq!(
+        organism.set_body_state(3).unwrap_err(),
+        OrganismError::BodyStateOutOfRange { index: 3, len: 3 }
+    );
+}
+
+#[test]
+fn bodies_grow_one_square_per_frame_toward_a_target() {
+    let mut organism = free_living(common::dot());
+    let plus = common::body(&[(0.0, 0.0), (1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0)]);
+    organism.set_target_body(Some(plus.clone()));
+    let mut rng = Pcg64::seed_from_u64(79);
+    for squares in 2..=5 {
+        organism.next_frame(1, &mut rng).unwrap();
+        assert_eq!(organism.body_squares().size(), squares);
+    }
+    assert!(organism.body_squares().covers(&plus));
+    assert_eq!(organism.target_body(), None);
+    // The grown shape is kept once the target is dropped.
+    organism.next_frame(1, &mut rng).unwrap();
+    assert_eq!(organism.body_squares().size(), 5);
+}
+
+#[test]
+fn set_location_rejects_cells_off_the_grid() {
+    let mut organism = Organism::new(0, common::dot()).unwrap();
+    let size = Vector2::new(10, 10);
+    let inside = Vector2::new(9, 0);
+    assert!(organism.set_location(inside, size).is_ok());
+    for outside in [Vector2::new(10, 0), Vector2::new(0, -1)] {
+        assert_eq!(
+            organism.set_location(outside, size).unwrap_err(),
+            OrganismError::OutOfBounds { location: outside }
+        );
+    }
+    assert_eq!(organism.location(), inside);
+}
+
+#[test]
+fn hungry_organisms_switch_to_their_feeding_body() {
+    let frame_body_size = |energy_fraction: f64| {
+        let genome = Genome::builder()
+            .body_states(vec![
+                common::body(&[(0.0, 0.0)]),
+                common::body(&[(0.0, 0.0), (1.0, 0.0)]),
+                common::body(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)]),
+            ])
+            .build()
+            .unwrap();
+        let mut organism = free_living(genome);
+        organism.update_attributes(|attributes| {
+            attributes.behavior_mode = BehaviorMode::FeedWhenHungry {
+                body_state: 2,
+                hunger: 0.5,
+            }
+        });
+        let drained = organism.energy() - (organism.energy() as f64 * energy_fraction) as isize;
+        organism.gain_energy(-drained);
+        let mut rng = Pcg64::seed_from_u64(94);
+        organism.next_frame(1, &mut rng).unwrap();
+        (
+            organism.behavior_state().hunger,
+            organism.body_squares().size(),
+        )
+    };
+    let (full_hunger, full_size) = frame_body_size(1.0);
+    let (hungry_hunger, hungry_size) = frame_body_size(0.2);
+    assert_eq!(full_hunger, 0.0);
+    assert!(hungry_hunger >= 0.5);
+    assert_eq!(full_size, 1);
+    assert_eq!(hungry_size, 3);
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "rayon")]
+
+mod common;
+
+use common::{fingerprint, Fingerprint};
+use nalgebra::Vector2;
+use opus::organism::Organism;
+use opus::world::World;
+
+fn run_on(threads: usize) -> Fingerprint {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .unwrap();
+    pool.install(|| {
+        let mut world = common::populated_world(60, 21, 300);
+        for _ in 0..50 {
+            world.step().unwrap();
+        }
+        fingerprint(&world)
+    })
+}
+
+#[test]
+fn thread_count_does_not_change_the_outcome() {
+    let serial = run_on(1);
+    assert!(!serial.is_empty());
+    assert_eq!(serial, run_on(4));
+}
+
+#[test]
+fn parallel_stats_match_the_serial_scan() {
+    let mut world = World::from_seed(Vector2::new(1000, 1000), 96);
+    let template = Organism::new(0, common::domino()).unwrap();
+    world.organisms = (0..100_000)
+        .map(|id| {
+            let mut organism = template.clone_as(id);
+            organism.update_attributes(|attributes| {
+                attributes.metabolism = (id % 13) as f32 * 0.07;
+                attributes.reproduction_rate = (id % 7) as f32 * 0.1;
+            });
+            organism.gain_energy(-(id % 997));
+            organism
+        })
+        .collect();
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build()
+        .unwrap();
+    let parallel = pool.install(|| world.stats_parallel());
+    assert_eq!(parallel.population, 100_000);
+    assert_eq!(parallel, world.stats());
+}
@@ -0,0 +1,132 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use opus::organism::Genome;
+use opus::world::{World, WorldError, CURRENT_FORMAT_VERSION};
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+use std::path::PathBuf;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("opus-{}-{}", std::process::id(), name))
+}
+
+fn read_saved(world: &World, name: &str) -> String {
+    let path = temp_path(name);
+    world.save_to_path(&path).unwrap();
+    let json = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    json
+}
+
+#[test]
+fn reloaded_worlds_step_like_the_original() {
+    let mut world = common::populated_world(30, 8, 40);
+    // Values without a short decimal form, which only survive the trip when
+    // floats are written and parsed exactly.
+    for (index, energy) in world.energy_field.iter_mut().enumerate() {
+        *energy = index as f64 / 7.0;
+    }
+    for _ in 0..5 {
+        world.step().unwrap();
+    }
+    let path = temp_path("reload.json");
+    world.save_to_path(&path).unwrap();
+    let mut reloaded = World::load_from_path(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    for _ in 0..20 {
+        world.step().unwrap();
+        reloaded.step().unwrap();
+    }
+    assert_eq!(
+        read_saved(&world, "original.json"),
+        read_saved(&reloaded, "reloaded.json")
+    );
+}
+
+#[test]
+fn runs_from_the_same_seed_save_identically() {
+    let run = |name: &str| {
+        let mut world = common::populated_world(40, 42, 60);
+        for _ in 0..100 {
+            world.step().unwrap();
+        }
+        read_saved(&world, name)
+    };
+    assert_eq!(run("first.json"), run("second.json"));
+}
+
+#[cfg(feature = "bincode")]
+#[test]
+fn binary_checkpoints_round_trip_and_are_smaller() {
+    let mut world = common::populated_world(30, 17, 60);
+    for _ in 0..10 {
+        world.step().unwrap();
+    }
+    let path = temp_path("checkpoint.bin");
+    world.save_binary(&path).unwrap();
+    let binary_size = std::fs::metadata(&path).unwrap().len() as usize;
+    let reloaded = World::load_binary(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let json = read_saved(&world, "checkpoint.json");
+    assert_eq!(read_saved(&reloaded, "reloaded-checkpoint.json"), json);
+    assert!(
+        binary_size < json.len(),
+        "binary {} bytes, json {} bytes",
+        binary_size,
+        json.len()
+    );
+}
+
+#[test]
+fn newer_format_versions_are_rejected() {
+    let world = common::populated_world(10, 19, 5);
+    let current = format!("\"format_version\":{}", CURRENT_FORMAT_VERSION);
+    let json = read_saved(&world, "current.json");
+    assert!(json.contains(&current));
+    let bumped = json.replacen(
+        &current,
+        &format!("\"format_version\":{}", CURRENT_FORMAT_VERSION + 1),
+        1,
+    );
+    let path = temp_path("bumped.json");
+    std::fs::write(&path, bumped).unwrap();
+    let result = World::load_from_path(&path);
+    std::fs::remove_file(&path).unwrap();
+    match result {
+        Err(WorldError::VersionMismatch { found, expected }) => {
+            assert_eq!(found, CURRENT_FORMAT_VERSION + 1);
+            assert_eq!(expected, CURRENT_FORMAT_VERSION);
+        }
+        Err(error) => panic!("expected a version mismatch, got {}", error),
+        Ok(_) => panic!("a newer format version was accepted"),
+    }
+}
+
+#[test]
+fn worlds_saved_before_versioning_still_load() {
+    let world = common::populated_world(10, 20, 5);
+    let current = format!("\"format_version\":{},", CURRENT_FORMAT_VERSION);
+    let json = read_saved(&world, "versioned.json");
+    assert!(json.contains(&current));
+    let unversioned = json.replacen(&current, "", 1);
+    let path = temp_path("unversioned.json");
+    std::fs::write(&path, unversioned).unwrap();
+    let loaded = World::load_from_path(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.format_version(), CURRENT_FORMAT_VERSION);
+}
+
+#[test]
+fn genomes_round_trip_through_json() {
+    let mut rng = Pcg64::seed_from_u64(7);
+    for _ in 0..20 {
+        let genome = Genome::random(&mut rng);
+        let json = serde_json::to_string(&genome).unwrap();
+        // Attribute types are tagged with their variant name.
+        assert!(json.contains("\"ReproductionRate\":"));
+        let decoded: Genome = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, genome);
+    }
+}
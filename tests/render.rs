@@ -0,0 +1,29 @@
+mod common;
+
+use nalgebra::Vector2;
+use opus::world::World;
+
+// A domino and a dot, three squares in all.
+fn small_world() -> World {
+    let mut world = World::from_seed(Vector2::new(6, 4), 23);
+    world.spawn(common::domino(), Vector2::new(1, 1)).unwrap();
+    world.spawn(common::dot(), Vector2::new(4, 2)).unwrap();
+    world
+}
+
+#[test]
+fn svg_draws_one_rect_per_square() {
+    let svg = small_world().to_svg();
+    assert!(svg.starts_with("<svg"));
+    assert_eq!(svg.matches("<rect").count(), 3);
+}
+
+#[test]
+fn rasterized_worlds_fill_an_rgba_buffer() {
+    let (width, height) = (30, 20);
+    let buffer = small_world().rasterize(width, height);
+    assert_eq!(buffer.len(), width * height * 4);
+    assert!(buffer.chunks(4).any(|pixel| pixel[3] != 0));
+    let empty = World::from_seed(Vector2::new(6, 4), 24).rasterize(width, height);
+    assert!(empty.iter().all(|&byte| byte == 0));
+}
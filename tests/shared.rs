@@ -0,0 +1,43 @@
+mod common;
+
+use opus::shared::SharedWorld;
+use std::thread;
+
+#[test]
+fn readers_see_whole_steps_while_another_thread_steps() {
+    let shared = SharedWorld::new(common::populated_world(40, 16, 80));
+    let stepper = {
+        let shared = shared.clone();
+        thread::spawn(move || {
+            for _ in 0..100 {
+                shared.step().unwrap();
+            }
+        })
+    };
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                let mut last_tick = 0;
+                for _ in 0..200 {
+                    // A step is never seen half done, so the stats always
+                    // cover the population as it stands.
+                    let (tick, population, stats) =
+                        shared.read(|world| (world.tick(), world.organisms.len(), world.stats()));
+                    assert!(tick >= last_tick);
+                    assert_eq!(stats.population, population);
+                    last_tick = tick;
+                }
+            })
+        })
+        .collect();
+    stepper.join().unwrap();
+    for reader in readers {
+        reader.join().unwrap();
+    }
+    assert_eq!(shared.read(|world| world.tick()), 100);
+    assert_eq!(
+        shared.read_stats().population,
+        shared.read(|world| world.organisms.len())
+    );
+}
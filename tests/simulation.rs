@@ -0,0 +1,26 @@
+mod common;
+
+use opus::organism::Attribute;
+use opus::simulation::{Simulation, StopReason};
+
+#[test]
+fn high_metabolism_worlds_die_out() {
+    let mut world = common::populated_world(20, 38, 30);
+    world.config.default_attributes = Attribute::builder().metabolism(1.0).build();
+    // Organisms spawned before the change keep their own attributes.
+    world.apply(|organism| organism.update_attributes(|attributes| attributes.metabolism = 1.0));
+    let mut simulation = Simulation::new(world).with_max_ticks(100_000);
+    let report = simulation.run_until(|_, _| false).unwrap();
+    assert_eq!(report.reason, StopReason::Extinct);
+    assert!(report.ticks < 100_000);
+    assert_eq!(report.stats.population, 0);
+}
+
+#[test]
+fn runs_stop_at_the_tick_cap() {
+    let world = common::populated_world(20, 39, 10);
+    let mut simulation = Simulation::new(world).with_max_ticks(5);
+    let report = simulation.run_until(|_, _| false).unwrap();
+    assert_eq!(report.reason, StopReason::MaxTicks);
+    assert_eq!(report.ticks, 5);
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use nalgebra::Vector2;
+use opus::spatial::SpatialGrid;
+use opus::world::World;
+
+fn brute_force(world: &World, pos: Vector2<isize>, radius: isize) -> Vec<usize> {
+    world
+        .organisms
+        .iter()
+        .enumerate()
+        .filter(|(_, organism)| {
+            let offset = organism.location() - pos;
+            radius >= 0 && offset.x * offset.x + offset.y * offset.y <= radius * radius
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+#[test]
+fn grid_matches_a_brute_force_scan() {
+    let world = common::populated_world(60, 19, 200);
+    assert!(world.organisms.len() > 50);
+    for cell_size in [1, 3, 8, 64] {
+        let grid = SpatialGrid::build(&world.organisms, cell_size);
+        for x in (-10..70).step_by(7) {
+            for y in (-10..70).step_by(9) {
+                let pos = Vector2::new(x, y);
+                for radius in [-1, 0, 1, 5, 12, 100] {
+                    assert_eq!(
+                        grid.neighbors_within(pos, radius),
+                        brute_force(&world, pos, radius),
+                        "cell size {} at ({}, {}) within {}",
+                        cell_size,
+                        x,
+                        y,
+                        radius
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn nearest_matches_a_brute_force_scan() {
+    let world = common::populated_world(60, 20, 200);
+    let grid = SpatialGrid::build(&world.organisms, 5);
+    for x in (-10..70).step_by(11) {
+        for y in (-10..70).step_by(13) {
+            let pos = Vector2::new(x, y);
+            let expected = world
+                .organisms
+                .iter()
+                .enumerate()
+                .map(|(index, organism)| {
+                    let offset = organism.location() - pos;
+                    (offset.x * offset.x + offset.y * offset.y, index)
+                })
+                .min()
+                .map(|(distance, index)| (index, distance));
+            assert_eq!(grid.nearest(pos, |_| false), expected);
+        }
+    }
+}
+
+#[test]
+fn nearest_organism_skips_the_excluded_id() {
+    let mut world = World::from_seed(Vector2::new(20, 20), 72);
+    let near = world.spawn(common::dot(), Vector2::new(5, 5)).unwrap();
+    let middle = world.spawn(common::dot(), Vector2::new(8, 9)).unwrap();
+    world.spawn(common::dot(), Vector2::new(18, 18)).unwrap();
+    let pos = Vector2::new(4, 5);
+    assert_eq!(world.nearest_organism(pos, None), Some((near, 1)));
+    // (8, 9) is sqrt(32) away, which rounds to 6.
+    assert_eq!(world.nearest_organism(pos, Some(near)), Some((middle, 6)));
+    assert_eq!(
+        World::from_seed(Vector2::new(5, 5), 72).nearest_organism(pos, None),
+        None
+    );
+}
+
+#[test]
+fn organism_at_finds_the_body_covering_a_cell() {
+    let mut world = World::from_seed(Vector2::new(10, 10), 98);
+    world.spawn(common::dot(), Vector2::new(1, 1)).unwrap();
+    let domino = world.spawn(common::domino(), Vector2::new(4, 4)).unwrap();
+    // The domino lies across (4, 4) and (5, 4).
+    assert_eq!(world.organism_at(Vector2::new(5, 4)), Some(domino));
+    assert_eq!(world.organism_at(Vector2::new(4, 4)), Some(domino));
+    assert_eq!(world.organism_at(Vector2::new(4, 5)), None);
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use nalgebra::Vector2;
+use opus::organism::{Genome, Organism};
+use opus::stats::{histogram, AttrSelector, PopulationStats, Summary};
+use opus::world::World;
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+// Organisms of one, two and three squares holding 100, 200 and 300 energy,
+// with metabolisms of 0.1, 0.2 and 0.3.
+fn trio() -> Vec<Organism> {
+    let shapes = [
+        vec![(0.0, 0.0)],
+        vec![(0.0, 0.0), (1.0, 0.0)],
+        vec![(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)],
+    ];
+    shapes
+        .iter()
+        .enumerate()
+        .map(|(index, shape)| {
+            let genome = Genome::builder()
+                .body_state(common::body(shape))
+                .build()
+                .unwrap();
+            let mut organism = Organism::new(index as isize, genome).unwrap();
+            let step = index as f32 + 1.0;
+            organism.update_attributes(|attributes| {
+                attributes.max_energy = 100 * step as isize;
+                attributes.metabolism = 0.1 * step;
+                attributes.reproduction_rate = 0.0;
+                attributes.mutation_rate = 0.0;
+            });
+            organism
+        })
+        .collect()
+}
+
+fn assert_summary(summary: Option<Summary>, mean: f64, min: f64, max: f64) {
+    let summary = summary.expect("a non-empty population");
+    for (actual, expected) in [(summary.mean, mean), (summary.min, min), (summary.max, max)] {
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+}
+
+#[test]
+fn stats_aggregate_each_attribute() {
+    let stats = PopulationStats::from_organisms(&trio());
+    assert_eq!(stats.population, 3);
+    assert_summary(stats.energy, 200.0, 100.0, 300.0);
+    assert_summary(stats.age, 0.0, 0.0, 0.0);
+    assert_summary(stats.body_size, 2.0, 1.0, 3.0);
+    assert_summary(stats.metabolism, 0.2, 0.1, 0.3);
+    assert_summary(stats.reproduction_rate, 0.0, 0.0, 0.0);
+    assert_summary(stats.mutation_rate, 0.0, 0.0, 0.0);
+    assert_eq!(PopulationStats::from_organisms(&[]).energy, None);
+}
+
+// Dots that pay nothing per frame, aged to each of `ages`.
+fn aged(ages: &[isize]) -> Vec<Organism> {
+    let mut rng = Pcg64::seed_from_u64(57);
+    ages.iter()
+        .map(|&age| {
+            let mut organism = Organism::new(0, common::dot()).unwrap();
+            organism.update_attributes(|attributes| {
+                attributes.metabolism = 0.0;
+                attributes.mutation_rate = 0.0;
+                attributes.reproduction_rate = 0.0;
+            });
+            for _ in 0..age {
+                organism.next_frame(1, &mut rng).unwrap();
+            }
+            organism
+        })
+        .collect()
+}
+
+#[test]
+fn ages_fall_into_equal_width_bins() {
+    let organisms = aged(&[0, 1, 2, 3, 4, 9, 10]);
+    assert_eq!(histogram(&organisms, AttrSelector::Age, 3), [4, 1, 2]);
+    // Equal values all land in the first bin.
+    assert_eq!(histogram(&aged(&[5, 5]), AttrSelector::Age, 3), [2, 0, 0]);
+}
+
+#[test]
+fn density_counts_organisms_per_block() {
+    let mut world = World::from_seed(Vector2::new(10, 7), 77);
+    for (x, y) in [(0, 0), (3, 3), (5, 1), (9, 6), (8, 4)] {
+        world.spawn(common::dot(), Vector2::new(x, y)).unwrap();
+    }
+    // Blocks of 4 leave a last column two cells wide and a last row three
+    // cells tall.
+    assert_eq!(world.density_grid(4), [vec![2, 1, 0], vec![0, 0, 2]]);
+}
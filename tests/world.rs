@@ -0,0 +1,724 @@
+mod common;
+
+use common::shared_cells;
+use nalgebra::Vector2;
+use opus::observer::SimObserver;
+use opus::organism::{Attribute, Genome, Organism};
+use opus::world::{BoundaryPolicy, CullPolicy, World, WorldError};
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+#[test]
+fn organisms_never_share_cells() {
+    let mut world = common::populated_world(40, 3, 80);
+    assert_eq!(shared_cells(&world), 0);
+    for tick in 0..300 {
+        world.step().unwrap();
+        assert_eq!(shared_cells(&world), 0, "overlap after tick {}", tick);
+    }
+}
+
+#[test]
+fn aborted_births_cost_the_parent_nothing() {
+    // Nine dots fill a 3x3 world, so no newborn ever finds room.
+    let mut world = World::from_seed(Vector2::new(3, 3), 5);
+    world.config.default_attributes = Attribute::builder()
+        .metabolism(0.0)
+        .movement_cost_factor(0.0)
+        .transition_cost_factor(0.0)
+        .mutation_rate(0.0)
+        .reproduction_rate(1.0)
+        .puberty_age(0)
+        .min_reproduction_energy(0)
+        .build();
+    for x in 0..3 {
+        for y in 0..3 {
+            world.spawn(common::dot(), Vector2::new(x, y)).unwrap();
+        }
+    }
+    let before: Vec<_> = world
+        .organisms
+        .iter()
+        .map(|o| (o.id(), o.energy()))
+        .collect();
+    let summary = world.step().unwrap();
+    assert_eq!(summary.births, 0);
+    for (id, energy) in before {
+        if let Some(organism) = world.get(id) {
+            assert_eq!(organism.energy(), energy, "organism {} was charged", id);
+        }
+    }
+}
+
+// A domino that stands up before lying down, and so moves by (-2, +2) where
+// common::domino moves by (+2, -2).
+fn standing_domino() -> Genome {
+    Genome::builder()
+        .body_states(vec![
+            common::body(&[(0.0, 0.0), (0.0, 1.0)]),
+            common::body(&[(0.0, 0.0), (1.0, 0.0)]),
+        ])
+        .build()
+        .unwrap()
+}
+
+// A domino next to one edge of a 10x10 world, where it lands under Clamp and
+// under Wrap. It keeps its shape for the first frame and steps off the grid on
+// the second, the first frame it is old enough to reproduce in.
+struct EdgeCase {
+    edge: &'static str,
+    genome: fn() -> Genome,
+    start: (isize, isize),
+    clamped: (isize, isize),
+    wrapped: (isize, isize),
+}
+
+const EDGE_CASES: [EdgeCase; 4] = [
+    EdgeCase {
+        edge: "+x",
+        genome: common::domino,
+        start: (8, 5),
+        clamped: (9, 3),
+        wrapped: (0, 3),
+    },
+    EdgeCase {
+        edge: "-y",
+        genome: common::domino,
+        start: (4, 1),
+        clamped: (6, 0),
+        wrapped: (6, 9),
+    },
+    EdgeCase {
+        edge: "-x",
+        genome: standing_domino,
+        start: (1, 4),
+        clamped: (0, 6),
+        wrapped: (9, 6),
+    },
+    EdgeCase {
+        edge: "+y",
+        genome: standing_domino,
+        start: (4, 8),
+        clamped: (2, 9),
+        wrapped: (2, 0),
+    },
+];
+
+fn edge_world(case: &EdgeCase, policy: BoundaryPolicy, reproduction_rate: f32) -> (World, isize) {
+    let mut world = World::from_seed(Vector2::new(10, 10), 11);
+    world.boundary_policy = policy;
+    world.config.default_attributes = Attribute::builder()
+        .metabolism(0.0)
+        .movement_cost_factor(0.0)
+        .transition_cost_factor(0.0)
+        .mutation_rate(0.0)
+        .reproduction_rate(reproduction_rate)
+        .puberty_age(2)
+        .min_reproduction_energy(0)
+        .build();
+    let (x, y) = case.start;
+    let id = world.spawn((case.genome)(), Vector2::new(x, y)).unwrap();
+    (world, id)
+}
+
+// Where the domino ends up after its second frame, if it survives.
+fn location_after_leaving(case: &EdgeCase, policy: BoundaryPolicy) -> Option<(isize, isize)> {
+    let (mut world, id) = edge_world(case, policy, 0.0);
+    world.step().unwrap();
+    world.step().unwrap();
+    world.get(id).map(|organism| {
+        let location = organism.location();
+        (location.x, location.y)
+    })
+}
+
+#[test]
+fn clamp_keeps_organisms_on_the_edge() {
+    for case in &EDGE_CASES {
+        assert_eq!(
+            location_after_leaving(case, BoundaryPolicy::Clamp),
+            Some(case.clamped),
+            "past {}",
+            case.edge
+        );
+    }
+}
+
+#[test]
+fn wrap_moves_organisms_to_the_far_edge() {
+    // Leaving past -x and -y wraps negative coordinates.
+    for case in &EDGE_CASES {
+        assert_eq!(
+            location_after_leaving(case, BoundaryPolicy::Wrap),
+            Some(case.wrapped),
+            "past {}",
+            case.edge
+        );
+    }
+}
+
+#[test]
+fn kill_removes_organisms_that_leave_the_grid() {
+    for case in &EDGE_CASES {
+        let (mut world, id) = edge_world(case, BoundaryPolicy::Kill, 0.0);
+        world.step().unwrap();
+        let summary = world.step().unwrap();
+        assert_eq!(summary.deaths, 1, "past {}", case.edge);
+        assert!(world.get(id).is_none());
+    }
+}
+
+#[test]
+fn parents_killed_by_the_boundary_have_no_offspring() {
+    for case in &EDGE_CASES {
+        let (mut world, _) = edge_world(case, BoundaryPolicy::Kill, 1.0);
+        world.step().unwrap();
+        let summary = world.step().unwrap();
+        assert_eq!(summary.births, 0, "past {}", case.edge);
+        assert!(world.organisms.is_empty());
+    }
+}
+
+#[test]
+fn feeding_moves_energy_out_of_the_field() {
+    let mut world = World::from_seed(Vector2::new(1, 1), 2);
+    world.config.default_attributes = Attribute::builder()
+        .metabolism(0.0)
+        .mutation_rate(0.0)
+        .reproduction_rate(0.0)
+        .build();
+    let id = world.spawn(common::dot(), Vector2::new(0, 0)).unwrap();
+    world.energy_field[0] = 50.0;
+    // Room for only part of what the cell holds.
+    let organism = world.get_mut(id).unwrap();
+    let room = 5;
+    let max_energy = organism.energy() + room;
+    organism.update_attributes(|attributes| attributes.max_energy = max_energy);
+    let total = world.total_energy();
+    world.step().unwrap();
+    assert_eq!(world.get(id).unwrap().energy(), max_energy);
+    assert_eq!(world.energy_field[0], 50.0 - room as f64);
+    assert_eq!(world.total_energy(), total);
+}
+
+#[test]
+fn lowering_max_energy_clamps_stored_energy() {
+    let mut world = World::from_seed(Vector2::new(1, 1), 2);
+    let id = world.spawn(common::dot(), Vector2::new(0, 0)).unwrap();
+    let organism = world.get_mut(id).unwrap();
+    organism.update_attributes(|attributes| {
+        attributes.max_energy = 1;
+        attributes.reproduction_rate = 2.0;
+    });
+    assert_eq!(organism.energy(), 1);
+    assert_eq!(organism.attributes().reproduction_rate, 1.0);
+}
+
+#[test]
+fn diffusion_does_not_change_total_energy() {
+    let mut world = World::from_seed(Vector2::new(7, 5), 4);
+    // Whole units that diffusion spreads into fractions. A floored total
+    // would drop a unit whenever the rounding error falls below the sum.
+    world.energy_field[12] = 40.0;
+    world.energy_field[30] = 3.0;
+    let total = world.total_energy();
+    for _ in 0..50 {
+        world.diffuse();
+        assert_eq!(world.total_energy(), total);
+    }
+    assert!((world.field_energy() - 43.0).abs() < 1e-9);
+}
+
+#[test]
+fn species_members_are_all_close_to_each_other() {
+    let mut world = World::from_seed(Vector2::new(10, 10), 9);
+    for (x, rate) in [(0, 0.5), (3, 0.3), (6, 0.7)] {
+        let genome = Genome::builder()
+            .reproduction_rate(rate)
+            .body_state(common::body(&[(0.0, 0.0)]))
+            .build()
+            .unwrap();
+        world.spawn(genome, Vector2::new(x, 0)).unwrap();
+    }
+    // The last two are each close to the first, but not to one another.
+    assert_eq!(world.cluster_species(0.3), vec![vec![0, 1], vec![2]]);
+}
+
+#[test]
+fn random_genomes_fit_any_template() {
+    let mut rng = Pcg64::seed_from_u64(13);
+    for template_size in [0, 20, 1000] {
+        let template = Attribute::builder().max_size(template_size).build();
+        for _ in 0..20 {
+            let genome = Genome::random_from_template(&template, &mut rng);
+            let organism = Organism::from_template(0, genome, &template).unwrap();
+            let max_size = organism.attributes().max_size;
+            assert!((6..=10).contains(&max_size), "max_size {}", max_size);
+        }
+    }
+}
+
+#[test]
+fn runs_from_the_same_seed_are_identical() {
+    let run = || {
+        let mut world = common::populated_world(40, 42, 60);
+        for _ in 0..100 {
+            world.step().unwrap();
+        }
+        common::fingerprint(&world)
+    };
+    let first = run();
+    assert!(!first.is_empty());
+    assert_eq!(first, run());
+}
+
+#[test]
+fn food_keeps_starving_organisms_alive_longer() {
+    let lifetime = |food: usize| {
+        // A single cell, so every piece of food lands on the organism.
+        let mut world = World::from_seed(Vector2::new(1, 1), 10);
+        world.config.default_attributes = Attribute::builder()
+            .metabolism(1.0)
+            .metabolism_exponent(1.0)
+            .max_age(100_000)
+            .mutation_rate(0.0)
+            .reproduction_rate(0.0)
+            .build();
+        world.spawn(common::dot(), Vector2::new(0, 0)).unwrap();
+        let mut rng = Pcg64::seed_from_u64(10);
+        world.spawn_food(food, &mut rng);
+        let mut ticks = 0;
+        while !world.organisms.is_empty() {
+            world.step().unwrap();
+            ticks += 1;
+        }
+        ticks
+    };
+    assert!(lifetime(3) > lifetime(0));
+}
+
+// A world whose organisms reproduce on every frame and pay for nothing.
+fn breeding_world(size: usize, seed: u64) -> World {
+    let mut world = World::from_seed(Vector2::new(size, size), seed);
+    world.config.default_attributes = Attribute::builder()
+        .metabolism(0.0)
+        .movement_cost_factor(0.0)
+        .transition_cost_factor(0.0)
+        .mutation_rate(0.0)
+        .reproduction_rate(1.0)
+        .puberty_age(0)
+        .min_reproduction_energy(0)
+        .build();
+    world
+}
+
+#[test]
+fn lineage_reaches_back_through_every_generation() {
+    let mut world = breeding_world(20, 14);
+    let founder = world.spawn(common::dot(), Vector2::new(10, 10)).unwrap();
+    world.step().unwrap();
+    world.step().unwrap();
+    let grandchild = world
+        .organisms
+        .iter()
+        .find(|organism| {
+            organism
+                .parent_id()
+                .and_then(|parent| world.get(parent))
+                .is_some_and(|parent| parent.parent_id() == Some(founder))
+        })
+        .expect("two generations were born");
+    let parent = grandchild.parent_id().unwrap();
+    assert_eq!(world.lineage_of(grandchild.id()), vec![parent, founder]);
+    assert!(world.lineage_of(founder).is_empty());
+}
+
+#[test]
+fn organisms_can_be_filtered_by_energy() {
+    let mut world = World::from_seed(Vector2::new(10, 10), 32);
+    for (x, max_energy) in [(0, 100), (3, 200), (6, 300)] {
+        let id = world.spawn(common::dot(), Vector2::new(x, 0)).unwrap();
+        world
+            .get_mut(id)
+            .unwrap()
+            .update_attributes(|attributes| attributes.max_energy = max_energy);
+    }
+    assert_eq!(world.organisms_where(|o| o.energy() > 150).count(), 2);
+    assert_eq!(world.organisms_where(|o| o.energy() > 1000).count(), 0);
+    assert_eq!(world.most_energetic().unwrap().energy(), 300);
+    assert!(world.oldest().is_some());
+}
+
+// A three-square predator whose squares start at `offset` on the x axis,
+// spawned so that its body covers cells (2..=4, 5), next to a dot at (5, 5).
+fn predation_world(offset: f64) -> (World, isize, isize) {
+    let mut world = World::from_seed(Vector2::new(30, 10), 33);
+    world.config.default_attributes = Attribute::builder()
+        .metabolism(0.0)
+        .mutation_rate(0.0)
+        .reproduction_rate(0.0)
+        .starting_energy_fraction(0.5)
+        .build();
+    world.predation_enabled = true;
+    let line = Genome::builder()
+        .body_state(common::body(&[
+            (offset, 0.0),
+            (offset + 1.0, 0.0),
+            (offset + 2.0, 0.0),
+        ]))
+        .build()
+        .unwrap();
+    let predator = world
+        .spawn(line, Vector2::new(2 - offset as isize, 5))
+        .unwrap();
+    let prey = world.spawn(common::dot(), Vector2::new(5, 5)).unwrap();
+    (world, predator, prey)
+}
+
+#[test]
+fn larger_organisms_eat_smaller_neighbors() {
+    for offset in [0.0, -20.0] {
+        let (mut world, predator, prey) = predation_world(offset);
+        let before = world.get(predator).unwrap().energy();
+        let summary = world.step().unwrap();
+        assert_eq!(summary.deaths, 1, "squares offset by {}", offset);
+        assert!(world.get(prey).is_none());
+        assert!(world.get(predator).unwrap().energy() > before);
+    }
+}
+
+#[derive(Default)]
+struct CountingObserver {
+    births: usize,
+    deaths: usize,
+}
+
+impl SimObserver for CountingObserver {
+    fn on_birth(&mut self, _child: &Organism) {
+        self.births += 1;
+    }
+
+    fn on_death(&mut self, _id: isize) {
+        self.deaths += 1;
+    }
+}
+
+#[test]
+fn observers_see_every_birth_and_death() {
+    let mut world = common::populated_world(30, 35, 60);
+    world.max_population = Some(80);
+    let (mut births, mut deaths) = (0, 0);
+    let mut observer = CountingObserver::default();
+    for _ in 0..200 {
+        let summary = world.step_with_observer(&mut observer).unwrap();
+        births += summary.births;
+        deaths += summary.deaths;
+    }
+    assert!(births > 0 && deaths > 0);
+    assert_eq!(observer.births, births);
+    assert_eq!(observer.deaths, deaths);
+}
+
+#[test]
+fn spawning_outside_the_grid_is_rejected() {
+    let mut world = World::from_seed(Vector2::new(10, 10), 49);
+    for location in [Vector2::new(10, 0), Vector2::new(0, -1)] {
+        assert!(matches!(
+            world.spawn(common::dot(), location),
+            Err(WorldError::OutOfBounds { location: rejected, .. }) if rejected == location
+        ));
+    }
+    assert!(world.organisms.is_empty());
+    assert!(world.spawn(common::dot(), Vector2::new(9, 9)).is_ok());
+}
+
+#[test]
+fn each_step_advances_the_tick() {
+    let mut world = common::populated_world(20, 54, 10);
+    assert_eq!(world.tick(), 0);
+    for _ in 0..5 {
+        world.step().unwrap();
+    }
+    assert_eq!(world.tick(), 5);
+}
+
+#[test]
+fn a_rich_cell_spreads_to_its_neighbours() {
+    let mut world = World::from_seed(Vector2::new(9, 9), 56);
+    let cell = |x: usize, y: usize| y * 9 + x;
+    world.energy_field[cell(4, 4)] = 100.0;
+    for _ in 0..3 {
+        world.step().unwrap();
+    }
+    for (x, y) in [(3, 4), (5, 4), (4, 3), (4, 5)] {
+        assert!(world.energy_field[cell(x, y)] > 0.0);
+        assert!(world.energy_field[cell(x, y)] < world.energy_field[cell(4, 4)]);
+    }
+    assert!(world.energy_field[cell(4, 4)] < 100.0);
+    assert!((world.field_energy() - 100.0).abs() < 1e-9);
+}
+
+#[test]
+fn removing_the_dead_hands_them_over_and_keeps_survivor_order() {
+    let mut world = World::from_seed(Vector2::new(10, 10), 62);
+    world.config.default_attributes = Attribute::builder()
+        .metabolism(0.0)
+        .mutation_rate(0.0)
+        .reproduction_rate(0.0)
+        .build();
+    let ids: Vec<_> = (0..4)
+        .map(|x| world.spawn(common::dot(), Vector2::new(2 * x, 0)).unwrap())
+        .collect();
+    for &id in &[ids[0], ids[2]] {
+        world
+            .get_mut(id)
+            .unwrap()
+            .update_attributes(|attributes| attributes.max_age = 1);
+    }
+    world.step().unwrap();
+    let dead: Vec<_> = world.remove_dead().iter().map(|o| o.id()).collect();
+    assert_eq!(dead, [ids[0], ids[2]]);
+    let survivors: Vec<_> = world.organisms.iter().map(|o| o.id()).collect();
+    assert_eq!(survivors, [ids[1], ids[3]]);
+    assert!(world.remove_dead().is_empty());
+}
+
+#[test]
+fn snapshot_diffs_list_moved_born_and_dead_ids() {
+    let mut world = World::from_seed(Vector2::new(20, 20), 66);
+    world.config.default_attributes = Attribute::builder()
+        .metabolism(0.0)
+        .movement_cost_factor(0.0)
+        .transition_cost_factor(0.0)
+        .mutation_rate(0.0)
+        .reproduction_rate(0.0)
+        .puberty_age(0)
+        .min_reproduction_energy(0)
+        .build();
+    let still = world.spawn(common::dot(), Vector2::new(2, 2)).unwrap();
+    let mover = world.spawn(common::domino(), Vector2::new(5, 10)).unwrap();
+    let doomed = world.spawn(common::dot(), Vector2::new(15, 2)).unwrap();
+    let parent = world.spawn(common::dot(), Vector2::new(15, 15)).unwrap();
+    // Dominoes keep their shape on the first frame and move on the second.
+    world.step().unwrap();
+    world
+        .get_mut(doomed)
+        .unwrap()
+        .update_attributes(|attributes| attributes.max_age = 2);
+    world
+        .get_mut(parent)
+        .unwrap()
+        .update_attributes(|attributes| attributes.reproduction_rate = 1.0);
+    let before = world.snapshot();
+    world.step().unwrap();
+    let diff = world.snapshot().diff(&before);
+    assert_eq!(diff.moved, [mover]);
+    assert_eq!(diff.died, [doomed]);
+    assert_eq!(diff.born.len(), 1);
+    assert_eq!(world.get(diff.born[0]).unwrap().parent_id(), Some(parent));
+    assert!(world.get(still).is_some());
+    assert!(world.snapshot().diff(&world.snapshot()).is_empty());
+}
+
+#[test]
+fn populations_over_the_cap_lose_their_weakest() {
+    let mut world = World::from_seed(Vector2::new(20, 20), 70);
+    world.config.default_attributes = Attribute::builder()
+        .metabolism(0.0)
+        .mutation_rate(0.0)
+        .reproduction_rate(0.0)
+        .build();
+    world.max_population = Some(3);
+    world.cull_policy = CullPolicy::LowestEnergy;
+    let mut ids = Vec::new();
+    for (x, energy) in [150, 110, 160, 130, 120, 140].into_iter().enumerate() {
+        let id = world
+            .spawn(common::dot(), Vector2::new(2 * x as isize, 0))
+            .unwrap();
+        world
+            .get_mut(id)
+            .unwrap()
+            .update_attributes(|attributes| attributes.max_energy = energy);
+        ids.push(id);
+    }
+    let summary = world.step().unwrap();
+    assert_eq!(world.organisms.len(), 3);
+    assert_eq!(summary.deaths, 3);
+    let survivors: Vec<_> = world.organisms.iter().map(|o| o.id()).collect();
+    assert_eq!(survivors, [ids[0], ids[2], ids[5]]);
+}
+
+#[test]
+fn organisms_inherit_the_world_default_metabolism() {
+    let mut world = breeding_world(20, 76);
+    world.config.default_attributes.metabolism = 0.3;
+    let founder = world.spawn(common::dot(), Vector2::new(10, 10)).unwrap();
+    assert_eq!(world.get(founder).unwrap().attributes().metabolism, 0.3);
+    world.step().unwrap();
+    assert!(world.organisms.len() > 1);
+    for organism in &world.organisms {
+        assert_eq!(organism.attributes().metabolism, 0.3);
+    }
+
+    let mut plain = World::from_seed(Vector2::new(20, 20), 76);
+    let id = plain.spawn(common::dot(), Vector2::new(10, 10)).unwrap();
+    assert_eq!(
+        plain.get(id).unwrap().attributes().metabolism,
+        Attribute::default().metabolism
+    );
+}
+
+#[test]
+fn apply_runs_a_closure_over_every_organism() {
+    let mut world = common::populated_world(30, 80, 20);
+    let before: Vec<_> = world.organisms.iter().map(|o| o.energy()).collect();
+    assert!(!before.is_empty());
+    world.apply(|organism| organism.gain_energy(-10));
+    let after: Vec<_> = world.organisms.iter().map(|o| o.energy()).collect();
+    for (before, after) in before.into_iter().zip(after) {
+        assert_eq!(after, before - 10);
+    }
+}
+
+#[test]
+fn the_fittest_genome_belongs_to_the_top_scorer() {
+    let mut world = World::from_seed(Vector2::new(20, 20), 86);
+    let genomes: Vec<_> = [100, 300, 200]
+        .into_iter()
+        .map(|max_energy| {
+            Genome::builder()
+                .max_energy(max_energy)
+                .body_state(common::body(&[(0.0, 0.0)]))
+                .build()
+                .unwrap()
+        })
+        .collect();
+    for (x, genome) in genomes.iter().enumerate() {
+        world
+            .spawn(genome.clone(), Vector2::new(2 * x as isize, 0))
+            .unwrap();
+    }
+    let by_energy = |organism: &Organism| organism.energy() as f64;
+    assert_eq!(world.fittest_genome(by_energy), Some(genomes[1].clone()));
+    let by_lowest_energy = |organism: &Organism| -organism.energy() as f64;
+    assert_eq!(
+        world.fittest_genome(by_lowest_energy),
+        Some(genomes[0].clone())
+    );
+    assert_eq!(
+        World::from_seed(Vector2::new(5, 5), 86).fittest_genome(by_energy),
+        None
+    );
+}
+
+#[test]
+fn elites_carry_into_the_next_generation_unmutated() {
+    let mut world = World::from_seed(Vector2::new(20, 20), 87);
+    world.config.default_attributes = Attribute::builder().mutation_rate(0.3).build();
+    for (x, max_energy) in [400, 900, 600, 500].into_iter().enumerate() {
+        let id = world
+            .spawn(common::domino(), Vector2::new(4 * x as isize, 5))
+            .unwrap();
+        world
+            .get_mut(id)
+            .unwrap()
+            .update_attributes(|attributes| attributes.max_energy = max_energy);
+    }
+    let elite = world.organisms[1].clone();
+    let mut rng = Pcg64::seed_from_u64(87);
+    world.next_generation(1, |organism| organism.energy() as f64, &mut rng);
+    assert_eq!(world.organisms.len(), 4);
+    let carried = world.get(elite.id()).expect("the top scorer survives");
+    assert_eq!(carried.genome(), elite.genome());
+    assert_eq!(carried.attributes(), elite.attributes());
+    assert_eq!(carried.energy(), elite.energy());
+    for child in world.organisms.iter().filter(|o| o.id() != elite.id()) {
+        assert_eq!(child.parent_id(), Some(elite.id()));
+    }
+}
+
+#[test]
+fn ids_still_resolve_after_others_die() {
+    let mut world = World::from_seed(Vector2::new(10, 10), 90);
+    world.config.default_attributes = Attribute::builder()
+        .metabolism(0.0)
+        .mutation_rate(0.0)
+        .reproduction_rate(0.0)
+        .build();
+    for x in 0..3 {
+        let id = world.spawn(common::dot(), Vector2::new(2 * x, 0)).unwrap();
+        world
+            .get_mut(id)
+            .unwrap()
+            .update_attributes(|attributes| attributes.max_age = 1);
+    }
+    let kept = world.spawn(common::dot(), Vector2::new(7, 7)).unwrap();
+    assert_eq!(world.organisms[3].id(), kept);
+    world.step().unwrap();
+    assert_eq!(world.organisms.len(), 1);
+    let organism = world.get(kept).expect("the survivor's id still resolves");
+    assert_eq!(organism.id(), kept);
+    assert_eq!(organism.location(), Vector2::new(7, 7));
+}
+
+#[test]
+fn a_closed_world_loses_exactly_its_upkeep_each_step() {
+    let mut world = World::from_seed(Vector2::new(20, 20), 81);
+    world.config.default_attributes = Attribute::builder()
+        .metabolism(0.5)
+        .metabolism_exponent(1.0)
+        .senescence_factor(0.0)
+        .compactness_factor(0.0)
+        .movement_cost_factor(0.0)
+        .transition_cost_factor(0.0)
+        .mutation_cost(0.0)
+        .mutation_rate(0.0)
+        .reproduction_rate(0.0)
+        .build();
+    for (x, squares) in [(0, 2), (5, 3), (10, 5)] {
+        let cells: Vec<_> = (0..squares).map(|square| (square as f64, 0.0)).collect();
+        let genome = Genome::builder()
+            .body_state(common::body(&cells))
+            .build()
+            .unwrap();
+        world.spawn(genome, Vector2::new(x, 10)).unwrap();
+    }
+    // With every other cost at zero, each frame costs metabolism times the
+    // body size, rounded down.
+    let upkeep = |world: &World| -> isize {
+        world
+            .organisms
+            .iter()
+            .map(|organism| {
+                let attributes = organism.attributes();
+                (attributes.metabolism as f64 * organism.body_squares().size() as f64) as isize
+            })
+            .sum()
+    };
+    assert_eq!(upkeep(&world), 1 + 1 + 2);
+    for _ in 0..20 {
+        let before = world.total_energy();
+        let expected = upkeep(&world);
+        let summary = world.step().unwrap();
+        assert_eq!(summary.deaths, 0);
+        assert_eq!(world.total_energy(), before - expected);
+    }
+    assert!(world.food.is_empty());
+    assert_eq!(world.field_energy(), 0.0);
+}
+
+#[test]
+fn a_new_generation_never_shares_cells() {
+    let mut world = World::from_seed(Vector2::new(20, 20), 87);
+    for x in 0..4 {
+        world
+            .spawn(common::dot(), Vector2::new(4 * x + 2, 10))
+            .unwrap();
+    }
+    let mut rng = Pcg64::seed_from_u64(87);
+    for _ in 0..5 {
+        world.next_generation(1, |organism| organism.energy() as f64, &mut rng);
+        assert_eq!(world.organisms.len(), 4);
+        assert_eq!(shared_cells(&world), 0);
+    }
+}